@@ -0,0 +1,98 @@
+use aes::{cipher::KeyInit, Aes128};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use cryptopals::{
+    set_1::{aes_128_ecb_decryt, aes_128_ecb_encrypt, xor_exact},
+    set_2::{
+        aes_128_cbc_decrypt, aes_128_cbc_decrypt_in_place, aes_128_cbc_encrypt,
+        aes_128_cbc_encrypt_in_place,
+    },
+};
+
+const KEY: [u8; 16] = *b"YELLOW SUBMARINE";
+const IV: [u8; 16] = [0; 16];
+const SIZES: [usize; 2] = [1 << 20, 4 << 20];
+
+/// The original CBC encryption, re-keying AES and allocating for every block
+fn rekeyed_encrypt(plain_text: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut cipher_text = IV.to_vec();
+    for chunk in plain_text.chunks(16) {
+        let input = xor_exact(&cipher_text, chunk).unwrap();
+        cipher_text = aes_128_ecb_encrypt(&input, &KEY).unwrap();
+        output.extend_from_slice(&cipher_text);
+    }
+    output
+}
+
+/// The original CBC decryption, re-keying AES and allocating for every block
+fn rekeyed_decrypt(cipher_text: &[u8]) -> Vec<u8> {
+    let mut output = Vec::new();
+    let mut previous_cipher_text = IV.to_vec();
+    for block in cipher_text.chunks(16) {
+        let almost_pt = aes_128_ecb_decryt(block, &KEY).unwrap();
+        output.extend_from_slice(&xor_exact(&almost_pt, &previous_cipher_text).unwrap());
+        previous_cipher_text = block.to_vec();
+    }
+    output
+}
+
+fn encrypt(c: &mut Criterion) {
+    let mut group = c.benchmark_group("aes_128_cbc_encrypt");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let plain_text = vec![0x42; size];
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("rekeyed", size), &plain_text, |b, pt| {
+            b.iter(|| rekeyed_encrypt(black_box(pt)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("allocating", size),
+            &plain_text,
+            |b, pt| b.iter(|| aes_128_cbc_encrypt(&IV, black_box(pt), KEY)),
+        );
+
+        let cipher = Aes128::new(&KEY.into());
+        let mut buffer = plain_text.clone();
+        group.bench_function(BenchmarkId::new("in_place", size), |b| {
+            b.iter(|| {
+                aes_128_cbc_encrypt_in_place(&IV, black_box(&mut buffer), &cipher).unwrap();
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn decrypt(c: &mut Criterion) {
+    let mut group = c.benchmark_group("aes_128_cbc_decrypt");
+    group.sample_size(10);
+
+    for size in SIZES {
+        let cipher_text = aes_128_cbc_encrypt(&IV, &vec![0x42; size], KEY);
+        group.throughput(Throughput::Bytes(size as u64));
+
+        group.bench_with_input(BenchmarkId::new("rekeyed", size), &cipher_text, |b, ct| {
+            b.iter(|| rekeyed_decrypt(black_box(ct)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("allocating", size),
+            &cipher_text,
+            |b, ct| b.iter(|| aes_128_cbc_decrypt(&IV, black_box(ct), KEY)),
+        );
+
+        let cipher = Aes128::new(&KEY.into());
+        let mut buffer = cipher_text.clone();
+        group.bench_function(BenchmarkId::new("in_place", size), |b| {
+            b.iter(|| {
+                aes_128_cbc_decrypt_in_place(&IV, black_box(&mut buffer), &cipher).unwrap();
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, encrypt, decrypt);
+criterion_main!(benches);
@@ -0,0 +1,147 @@
+//! Block ciphers the modes in [crate::modes] can be built on
+
+use aes::{
+    cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit},
+    Aes128, Aes192, Aes256,
+};
+
+use crate::fips_197;
+
+/// A keyed permutation of fixed size blocks
+pub trait BlockCipher {
+    /// Size of a block in bytes
+    const BLOCK_SIZE: usize;
+
+    /// The key the cipher is created from
+    type Key: ?Sized;
+
+    /// Creates the cipher, expanding the key once
+    fn new(key: &Self::Key) -> Self
+    where
+        Self: Sized;
+
+    /// Encrypts a single block of [BlockCipher::BLOCK_SIZE] bytes in place
+    fn encrypt_block(&self, block: &mut [u8]);
+
+    /// Decrypts a single block of [BlockCipher::BLOCK_SIZE] bytes in place
+    fn decrypt_block(&self, block: &mut [u8]);
+}
+
+macro_rules! impl_block_cipher_for_aes_crate {
+    ($cipher:ty, $key_len:literal) => {
+        impl BlockCipher for $cipher {
+            const BLOCK_SIZE: usize = 16;
+            type Key = [u8; $key_len];
+
+            fn new(key: &Self::Key) -> Self {
+                <$cipher as KeyInit>::new(&GenericArray::from(*key))
+            }
+
+            fn encrypt_block(&self, block: &mut [u8]) {
+                BlockEncrypt::encrypt_block(self, GenericArray::from_mut_slice(block));
+            }
+
+            fn decrypt_block(&self, block: &mut [u8]) {
+                BlockDecrypt::decrypt_block(self, GenericArray::from_mut_slice(block));
+            }
+        }
+    };
+}
+
+impl_block_cipher_for_aes_crate!(Aes128, 16);
+impl_block_cipher_for_aes_crate!(Aes192, 24);
+impl_block_cipher_for_aes_crate!(Aes256, 32);
+
+/// Our own AES, the key size is picked by the length of the key
+impl BlockCipher for fips_197::Aes {
+    const BLOCK_SIZE: usize = fips_197::BLOCK_SIZE;
+    type Key = [u8];
+
+    /// Panics if the key is not 16, 24 or 32 bytes, use [fips_197::Aes::new] to handle that
+    fn new(key: &[u8]) -> Self {
+        fips_197::Aes::new(key).expect("AES keys are 16, 24 or 32 bytes")
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        fips_197::Aes::encrypt_block(self, block.try_into().unwrap());
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        fips_197::Aes::decrypt_block(self, block.try_into().unwrap());
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    /// A toy cipher with 8 byte blocks, adds the key and rotates the block
+    pub(crate) struct ToyCipher {
+        key: [u8; 8],
+    }
+
+    impl BlockCipher for ToyCipher {
+        const BLOCK_SIZE: usize = 8;
+        type Key = [u8; 8];
+
+        fn new(key: &[u8; 8]) -> Self {
+            Self { key: *key }
+        }
+
+        fn encrypt_block(&self, block: &mut [u8]) {
+            for (byte, key_byte) in block.iter_mut().zip(self.key) {
+                *byte = byte.wrapping_add(key_byte);
+            }
+            block.rotate_left(3);
+        }
+
+        fn decrypt_block(&self, block: &mut [u8]) {
+            block.rotate_right(3);
+            for (byte, key_byte) in block.iter_mut().zip(self.key) {
+                *byte = byte.wrapping_sub(key_byte);
+            }
+        }
+    }
+
+    fn round_trip<C: BlockCipher>(cipher: &C) -> Vec<u8> {
+        let plain_text = (0..C::BLOCK_SIZE as u8).collect::<Vec<u8>>();
+        let mut block = plain_text.clone();
+
+        cipher.encrypt_block(&mut block);
+        assert_ne!(plain_text, block);
+        let cipher_text = block.clone();
+
+        cipher.decrypt_block(&mut block);
+        assert_eq!(plain_text, block);
+        cipher_text
+    }
+
+    #[test]
+    fn fips_197_matches_aes_crate() {
+        let key: [u8; 32] = rand::thread_rng().gen();
+
+        assert_eq!(
+            round_trip(&<Aes128 as BlockCipher>::new(
+                &key[..16].try_into().unwrap()
+            )),
+            round_trip(&<fips_197::Aes as BlockCipher>::new(&key[..16]))
+        );
+        assert_eq!(
+            round_trip(&<Aes192 as BlockCipher>::new(
+                &key[..24].try_into().unwrap()
+            )),
+            round_trip(&<fips_197::Aes as BlockCipher>::new(&key[..24]))
+        );
+        assert_eq!(
+            round_trip(&<Aes256 as BlockCipher>::new(&key)),
+            round_trip(&<fips_197::Aes as BlockCipher>::new(&key))
+        );
+    }
+
+    #[test]
+    fn toy_cipher() {
+        round_trip(&ToyCipher::new(b"toy key!"));
+    }
+}
@@ -0,0 +1,13 @@
+//! ChaCha20, Poly1305 and the ChaCha20-Poly1305 AEAD, from RFC 8439
+
+mod aead;
+mod chacha20;
+mod nonce_reuse;
+mod poly1305;
+
+pub use aead::{chacha20_poly1305_open, chacha20_poly1305_seal, poly1305_key_gen};
+pub use chacha20::{chacha20_apply, chacha20_block};
+pub use nonce_reuse::{
+    chacha20_poly1305_forge, recover_poly1305_key_candidates, recover_reused_keystream,
+};
+pub use poly1305::poly1305_mac;
@@ -0,0 +1,128 @@
+//! The ChaCha20-Poly1305 AEAD construction
+
+use super::{
+    chacha20::{chacha20_apply, chacha20_block},
+    poly1305::poly1305_mac,
+};
+
+/// The Poly1305 key for a message, the first half of keystream block zero
+pub fn poly1305_key_gen(key: &[u8; 32], nonce: &[u8; 12]) -> [u8; 32] {
+    chacha20_block(key, 0, nonce)[..32].try_into().unwrap()
+}
+
+/// What the tag covers, the additional data and cipher text each zero padded to 16 bytes, then their little endian
+/// lengths
+pub(super) fn mac_data(aad: &[u8], cipher_text: &[u8]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(aad.len() + cipher_text.len() + 48);
+    for text in [aad, cipher_text] {
+        data.extend_from_slice(text);
+        data.resize(data.len().next_multiple_of(16), 0);
+    }
+    data.extend_from_slice(&(aad.len() as u64).to_le_bytes());
+    data.extend_from_slice(&(cipher_text.len() as u64).to_le_bytes());
+    data
+}
+
+/// Encrypts and authenticates the plain text, and authenticates the additional data, the cipher text followed by the
+/// 16 byte tag
+///
+/// Returning None indicates the plain text is too long for the block counter
+pub fn chacha20_poly1305_seal(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    aad: &[u8],
+    plain_text: &[u8],
+) -> Option<Vec<u8>> {
+    let mut sealed = chacha20_apply(key, 1, nonce, plain_text)?;
+    let tag = poly1305_mac(&poly1305_key_gen(key, nonce), &mac_data(aad, &sealed));
+    sealed.extend_from_slice(&tag);
+    Some(sealed)
+}
+
+/// Checks the tag at the end of the sealed text and decrypts the cipher text before it
+///
+/// Returning None indicates the sealed text is shorter than a tag or the tag doesn't match
+pub fn chacha20_poly1305_open(
+    key: &[u8; 32],
+    nonce: &[u8; 12],
+    aad: &[u8],
+    sealed: &[u8],
+) -> Option<Vec<u8>> {
+    let (cipher_text, received_tag) = sealed.split_at(sealed.len().checked_sub(16)?);
+    let tag = poly1305_mac(&poly1305_key_gen(key, nonce), &mac_data(aad, cipher_text));
+    // Compare every byte, so the time taken doesn't give away how much of the tag matched
+    let difference = tag
+        .iter()
+        .zip(received_tag)
+        .fold(0, |difference, (a, b)| difference | (a ^ b));
+    if difference != 0 {
+        return None;
+    }
+
+    chacha20_apply(key, 1, nonce, cipher_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set_1::hex_to_bytes;
+
+    /// The key 80 81 .. 9f the RFC's AEAD examples use
+    fn key() -> [u8; 32] {
+        std::array::from_fn(|i| 0x80 + i as u8)
+    }
+
+    #[test]
+    fn rfc_8439_key_gen() {
+        let nonce = hex_to_bytes("000000000001020304050607")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            hex_to_bytes("8ad5a08b905f81cc815040274ab29471a833b637e3fd0da508dbb8e2fdd1a646")
+                .unwrap(),
+            poly1305_key_gen(&key(), &nonce)
+        );
+    }
+
+    #[test]
+    fn rfc_8439_aead() {
+        let nonce = hex_to_bytes("070000004041424344454647")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let aad = hex_to_bytes("50515253c0c1c2c3c4c5c6c7").unwrap();
+        let plain_text =
+            b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for \
+                           the future, sunscreen would be it.";
+        let sealed = hex_to_bytes(
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6\
+             3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36\
+             92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc\
+             3ff4def08e4b7a9de576d26586cec64b6116\
+             1ae10b594f09e26a7e902ecbd0600691",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(sealed.clone()),
+            chacha20_poly1305_seal(&key(), &nonce, &aad, plain_text)
+        );
+        assert_eq!(
+            Some(plain_text.to_vec()),
+            chacha20_poly1305_open(&key(), &nonce, &aad, &sealed)
+        );
+
+        let mut tampered = sealed.clone();
+        tampered[3] ^= 0x20;
+        assert_eq!(
+            None,
+            chacha20_poly1305_open(&key(), &nonce, &aad, &tampered)
+        );
+        assert_eq!(None, chacha20_poly1305_open(&key(), &nonce, b"", &sealed));
+        assert_eq!(
+            None,
+            chacha20_poly1305_open(&key(), &nonce, &aad, &sealed[..15])
+        );
+    }
+}
@@ -0,0 +1,141 @@
+//! The ChaCha20 stream cipher
+
+/// "expand 32-byte k"
+const CONSTANTS: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+/// One 64 byte block of keystream
+///
+/// The state is the constants, key, block counter and nonce as little endian words. Twenty rounds alternate between
+/// mixing its columns and its diagonals, then the original state is added back in.
+pub fn chacha20_block(key: &[u8; 32], counter: u32, nonce: &[u8; 12]) -> [u8; 64] {
+    let mut initial = [0; 16];
+    initial[..4].copy_from_slice(&CONSTANTS);
+    for (word, bytes) in initial[4..12].iter_mut().zip(key.chunks(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    initial[12] = counter;
+    for (word, bytes) in initial[13..].iter_mut().zip(nonce.chunks(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+
+    let mut state = initial;
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    let mut block = [0; 64];
+    for ((bytes, word), initial) in block.chunks_mut(4).zip(state).zip(initial) {
+        bytes.copy_from_slice(&word.wrapping_add(initial).to_le_bytes());
+    }
+    block
+}
+
+/// Encrypts or decrypts the text with the keystream starting at block `counter`
+///
+/// Returning None indicates the text is too long for the 32 bit block counter
+pub fn chacha20_apply(
+    key: &[u8; 32],
+    counter: u32,
+    nonce: &[u8; 12],
+    text: &[u8],
+) -> Option<Vec<u8>> {
+    let blocks = text.len().div_ceil(64) as u64;
+    if u64::from(counter) + blocks > 1 << 32 {
+        return None;
+    }
+
+    Some(
+        text.chunks(64)
+            .zip(counter..=u32::MAX)
+            .flat_map(|(chunk, counter)| {
+                let keystream = chacha20_block(key, counter, nonce);
+                chunk
+                    .iter()
+                    .zip(keystream)
+                    .map(|(byte, keystream_byte)| byte ^ keystream_byte)
+                    .collect::<Vec<u8>>()
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set_1::hex_to_bytes;
+
+    /// The key 00 01 .. 1f the RFC's examples use
+    fn key() -> [u8; 32] {
+        std::array::from_fn(|i| i as u8)
+    }
+
+    #[test]
+    fn rfc_8439_quarter_round() {
+        let mut state = [0; 16];
+        state[..4].copy_from_slice(&[0x11111111, 0x01020304, 0x9b8d6f43, 0x01234567]);
+        quarter_round(&mut state, 0, 1, 2, 3);
+        assert_eq!([0xea2a92f4, 0xcb1cf8ce, 0x4581472e, 0x5881c4bb], state[..4]);
+    }
+
+    #[test]
+    fn rfc_8439_block() {
+        let nonce = hex_to_bytes("000000090000004a00000000").unwrap();
+        assert_eq!(
+            hex_to_bytes(
+                "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e\
+                 d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e"
+            )
+            .unwrap(),
+            chacha20_block(&key(), 1, &nonce.try_into().unwrap())
+        );
+    }
+
+    #[test]
+    fn rfc_8439_encryption() {
+        let nonce = hex_to_bytes("000000000000004a00000000")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        let plain_text =
+            b"Ladies and Gentlemen of the class of '99: If I could offer you only one tip for \
+                           the future, sunscreen would be it.";
+        let cipher_text = hex_to_bytes(
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b\
+             f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8\
+             07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736\
+             5af90bbf74a35be6b40b8eedf2785e42874d",
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(cipher_text.clone()),
+            chacha20_apply(&key(), 1, &nonce, plain_text)
+        );
+        assert_eq!(
+            Some(plain_text.to_vec()),
+            chacha20_apply(&key(), 1, &nonce, &cipher_text)
+        );
+
+        assert_eq!(Some(vec![]), chacha20_apply(&key(), u32::MAX, &nonce, &[]));
+        assert!(chacha20_apply(&key(), u32::MAX, &nonce, &[0; 64]).is_some());
+        assert_eq!(None, chacha20_apply(&key(), u32::MAX, &nonce, &[0; 65]));
+    }
+}
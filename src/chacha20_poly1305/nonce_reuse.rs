@@ -0,0 +1,268 @@
+//! What a reused nonce gives away
+//!
+//! The same key and nonce give the same keystream, so the cipher texts XOR to the XOR of the plain texts and enough
+//! of them give up the keystream to the same statistics as repeating key XOR. They also share the Poly1305 key, and
+//! the difference of two tags is a polynomial in r with known coefficients, so finding its roots modulo 2^130 - 5
+//! recovers r, then s, after which any cipher text can be given a valid tag.
+
+use std::ops::{Add, Mul, Sub};
+
+use num_bigint::{BigInt, BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::Rng;
+
+use super::{
+    aead::mac_data,
+    poly1305::{message_blocks, poly1305_mac, prime, R_CLAMP},
+};
+use crate::{
+    polynomial::{Field, Polynomial},
+    set_1::break_single_char_xor,
+};
+
+/// Recovers the keystream the cipher texts were encrypted with, as far as the longest of them
+///
+/// Every position is a single byte XOR over the cipher texts long enough to reach it, broken by scoring the
+/// candidate plain texts as English. Positions only a few cipher texts reach are little better than a guess.
+pub fn recover_reused_keystream(cipher_texts: &[Vec<u8>]) -> Vec<u8> {
+    let longest = cipher_texts.iter().map(Vec::len).max().unwrap_or(0);
+    (0..longest)
+        .map(|i| {
+            let column: Vec<u8> = cipher_texts
+                .iter()
+                .filter_map(|c| c.get(i))
+                .copied()
+                .collect();
+            break_single_char_xor(&column)
+        })
+        .collect()
+}
+
+/// Recovers the possible Poly1305 keys from two sealed messages under the same key and nonce
+///
+/// With `a` and `b` the values of the two polynomials at r modulo p, the tags are `a + s` and `b + s` modulo 2^128,
+/// so `a - b` is the difference of the tags plus a multiple of 2^128 between -4 and 4. Each multiple gives a
+/// polynomial whose roots are candidates for r, and only those that survive clamping are kept.
+pub fn recover_poly1305_key_candidates(
+    (first_aad, first_sealed): (&[u8], &[u8]),
+    (second_aad, second_sealed): (&[u8], &[u8]),
+) -> Vec<[u8; 32]> {
+    let (Some(first_split), Some(second_split)) = (
+        first_sealed.len().checked_sub(16),
+        second_sealed.len().checked_sub(16),
+    ) else {
+        return Vec::new();
+    };
+    let (first_cipher_text, first_tag) = first_sealed.split_at(first_split);
+    let (second_cipher_text, second_tag) = second_sealed.split_at(second_split);
+    let first_tag = BigUint::from_bytes_le(first_tag);
+    let second_tag = BigUint::from_bytes_le(second_tag);
+
+    let first = mac_polynomial(&mac_data(first_aad, first_cipher_text));
+    let second = mac_polynomial(&mac_data(second_aad, second_cipher_text));
+    let difference = &first - &second;
+
+    let p = BigInt::from(prime().clone());
+    let two_128 = BigUint::one() << 128u32;
+    let tag_difference = BigInt::from(first_tag.clone()) - BigInt::from(second_tag.clone());
+    let mut keys = Vec::new();
+    for multiple in -4..=4 {
+        let constant = (&tag_difference + BigInt::from(multiple) * BigInt::from(two_128.clone()))
+            .mod_floor(&p);
+        let polynomial =
+            &difference - &Polynomial::constant(Fp1305(constant.to_biguint().unwrap()));
+        if polynomial.degree().is_none_or(|degree| degree == 0) {
+            continue;
+        }
+
+        for Fp1305(r) in polynomial.roots().unwrap() {
+            let Some(r) = u128::try_from(&r).ok().filter(|r| r & !R_CLAMP == 0) else {
+                continue;
+            };
+            let r_element = Fp1305(BigUint::from(r));
+            let s = (&first_tag + &two_128 - first.evaluate(&r_element).0 % &two_128) % &two_128;
+            if (second.evaluate(&r_element).0 + &s) % &two_128 != second_tag {
+                continue;
+            }
+
+            let mut key = [0; 32];
+            key[..16].copy_from_slice(&r.to_le_bytes());
+            key[16..].copy_from_slice(&u128::try_from(&s).unwrap().to_le_bytes());
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+    keys
+}
+
+/// Seals a cipher text with a recovered Poly1305 key, the nonce it was recovered for is implied
+pub fn chacha20_poly1305_forge(one_time_key: &[u8; 32], aad: &[u8], cipher_text: &[u8]) -> Vec<u8> {
+    let tag = poly1305_mac(one_time_key, &mac_data(aad, cipher_text));
+    [cipher_text, &tag].concat()
+}
+
+/// An integer modulo Poly1305's prime 2^130 - 5
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Fp1305(BigUint);
+
+impl Add for Fp1305 {
+    type Output = Fp1305;
+
+    fn add(self, rhs: Fp1305) -> Fp1305 {
+        Fp1305((self.0 + rhs.0) % prime())
+    }
+}
+
+impl Sub for Fp1305 {
+    type Output = Fp1305;
+
+    fn sub(self, rhs: Fp1305) -> Fp1305 {
+        let p = prime();
+        Fp1305((self.0 + p - rhs.0) % p)
+    }
+}
+
+impl Mul for Fp1305 {
+    type Output = Fp1305;
+
+    fn mul(self, rhs: Fp1305) -> Fp1305 {
+        Fp1305(self.0 * rhs.0 % prime())
+    }
+}
+
+impl Field for Fp1305 {
+    fn zero() -> Self {
+        Fp1305(BigUint::zero())
+    }
+
+    fn one() -> Self {
+        Fp1305(BigUint::one())
+    }
+
+    /// By Fermat's little theorem, a^(p - 2)
+    fn inverse(&self) -> Option<Self> {
+        if self.0.is_zero() {
+            return None;
+        }
+        let p = prime();
+        Some(Fp1305(self.0.modpow(&(p - 2u32), p)))
+    }
+
+    fn order() -> BigUint {
+        prime().clone()
+    }
+
+    fn characteristic() -> BigUint {
+        prime().clone()
+    }
+
+    fn from_integer(n: usize) -> Self {
+        Fp1305(BigUint::from(n) % prime())
+    }
+
+    /// a^p = a in a prime field
+    fn pth_root(&self) -> Self {
+        self.clone()
+    }
+
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Fp1305(rng.gen_biguint_below(prime()))
+    }
+}
+
+/// The polynomial the MAC evaluates at r, which has no constant term
+fn mac_polynomial(message: &[u8]) -> Polynomial<Fp1305> {
+    let mut coefficients: Vec<Fp1305> = message_blocks(message).map(Fp1305).collect();
+    coefficients.push(Fp1305::zero());
+    coefficients.reverse();
+    Polynomial::new(coefficients)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+    use crate::{
+        chacha20_poly1305::{
+            chacha20_apply, chacha20_poly1305_open, chacha20_poly1305_seal, poly1305_key_gen,
+        },
+        set_1::{base64_to_bytes, xor_repeat},
+    };
+
+    /// The lines of the song set 1's challenge 6 decrypts to
+    fn lyrics() -> Vec<Vec<u8>> {
+        let mut cipher_text = String::new();
+        std::fs::File::open("src/set_1/challenge_6/encrypted_file.txt")
+            .unwrap()
+            .read_to_string(&mut cipher_text)
+            .unwrap();
+        cipher_text.retain(|c| !c.is_whitespace());
+        let plain_text = xor_repeat(
+            &base64_to_bytes(&cipher_text),
+            b"Terminator X: Bring the noise",
+        )
+        .unwrap();
+
+        plain_text
+            .split(|&b| b == b'\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| line.to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn keystream_reuse() {
+        let key = rand::random();
+        let nonce = rand::random();
+        let lines = lyrics();
+        let cipher_texts: Vec<Vec<u8>> = lines
+            .iter()
+            .map(|line| chacha20_apply(&key, 1, &nonce, line).unwrap())
+            .collect();
+
+        let keystream = recover_reused_keystream(&cipher_texts);
+        let expected = chacha20_apply(&key, 1, &nonce, &vec![0; keystream.len()]).unwrap();
+        // Nearly every line reaches the first 20 bytes, so the statistics get those right, except for the first
+        // which is mostly capital letters
+        assert_eq!(expected[1..20], keystream[1..20]);
+        assert_eq!(Vec::<u8>::new(), recover_reused_keystream(&[]));
+    }
+
+    #[test]
+    fn poly1305_key_recovery() {
+        let key = rand::random();
+        let nonce = rand::random();
+        let first_plain_text = b"Pay Alice 100 dollars on the first of the month";
+        let first = chacha20_poly1305_seal(&key, &nonce, b"invoice 1", first_plain_text).unwrap();
+        let second =
+            chacha20_poly1305_seal(&key, &nonce, b"invoice 2", b"Pay Bob 20 dollars today")
+                .unwrap();
+
+        let candidates =
+            recover_poly1305_key_candidates((b"invoice 1", &first), (b"invoice 2", &second));
+        // Only the bits of r that survive clamping can be recovered
+        let mut one_time_key = poly1305_key_gen(&key, &nonce);
+        let r = u128::from_le_bytes(one_time_key[..16].try_into().unwrap()) & R_CLAMP;
+        one_time_key[..16].copy_from_slice(&r.to_le_bytes());
+        assert!(candidates.contains(&one_time_key));
+
+        // Knowing the first plain text gives its keystream, to change the amount and forge a tag for it
+        let mut cipher_text = first[..first_plain_text.len()].to_vec();
+        for (c, (a, b)) in cipher_text[10..13]
+            .iter_mut()
+            .zip(b"100".iter().zip(b"999"))
+        {
+            *c ^= a ^ b;
+        }
+        let forged = chacha20_poly1305_forge(&candidates[0], b"invoice 3", &cipher_text);
+        assert_eq!(
+            Some(b"Pay Alice 999 dollars on the first of the month".to_vec()),
+            chacha20_poly1305_open(&key, &nonce, b"invoice 3", &forged)
+        );
+
+        assert!(recover_poly1305_key_candidates((b"", &first), (b"", &[0; 15])).is_empty());
+    }
+}
@@ -0,0 +1,75 @@
+//! The Poly1305 one-time authenticator
+
+use std::sync::OnceLock;
+
+use num_bigint::BigUint;
+use num_traits::One;
+
+/// The bits of r that are kept, clearing the top four bits of every fourth byte and the bottom two of the others
+pub(super) const R_CLAMP: u128 = 0x0ffffffc0ffffffc0ffffffc0fffffff;
+
+/// The prime 2^130 - 5 the polynomial is evaluated modulo, computed once
+pub(super) fn prime() -> &'static BigUint {
+    static PRIME: OnceLock<BigUint> = OnceLock::new();
+    PRIME.get_or_init(|| (BigUint::one() << 130u32) - 5u32)
+}
+
+/// The message split into 16 byte little endian numbers, each with a one bit added above its top byte
+pub(super) fn message_blocks(message: &[u8]) -> impl Iterator<Item = BigUint> + '_ {
+    message
+        .chunks(16)
+        .map(|chunk| BigUint::from_bytes_le(chunk) + (BigUint::one() << (8 * chunk.len())))
+}
+
+/// The tag of the message under a one-time key, r || s
+///
+/// The message blocks are the coefficients of a polynomial evaluated at r modulo 2^130 - 5, with s added to the low
+/// 128 bits of the result.
+pub fn poly1305_mac(key: &[u8; 32], message: &[u8]) -> [u8; 16] {
+    let r = u128::from_le_bytes(key[..16].try_into().unwrap()) & R_CLAMP;
+    let s = u128::from_le_bytes(key[16..].try_into().unwrap());
+    let (r, p) = (BigUint::from(r), prime());
+
+    let accumulator = message_blocks(message).fold(BigUint::ZERO, |accumulator, block| {
+        (accumulator + block) * &r % p
+    });
+    let tag = (accumulator + s) % (BigUint::one() << 128u32);
+
+    let mut bytes = [0; 16];
+    let tag = tag.to_bytes_le();
+    bytes[..tag.len()].copy_from_slice(&tag);
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set_1::hex_to_bytes;
+
+    #[test]
+    fn rfc_8439_mac() {
+        let key = hex_to_bytes("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+            .unwrap();
+        assert_eq!(
+            hex_to_bytes("a8061dc1305136c6c22b8baf0c0127a9").unwrap(),
+            poly1305_mac(
+                &key.try_into().unwrap(),
+                b"Cryptographic Forum Research Group"
+            )
+        );
+    }
+
+    /// The accumulator lands just above 2^128 once s is added, only the low bits are kept
+    #[test]
+    fn tag_wraps() {
+        let mut key = [0; 32];
+        key[16..].copy_from_slice(&[0xff; 16]);
+        // r is zero, so the tag is s
+        assert_eq!([0xff; 16], poly1305_mac(&key, b"anything"));
+
+        key[0] = 1;
+        key[16..].copy_from_slice(&[0; 16]);
+        // r is one, so a single full block of ff is the tag before adding the high bit, which is dropped
+        assert_eq!([0xff; 16], poly1305_mac(&key, &[0xff; 16]));
+    }
+}
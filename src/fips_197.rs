@@ -0,0 +1,515 @@
+//! AES as specified in FIPS 197 (docs/AES.NIST.FIPS.197-upd1.pdf), with every step of the cipher exposed
+//!
+//! The state is kept in the order of the input bytes, `state[r + 4c]` being row r of column c, so it can be read
+//! straight from or written straight to a block. Nothing here is constant time, it is for experimenting with the
+//! cipher, not for protecting anything.
+
+/// Size of an AES block in bytes
+pub const BLOCK_SIZE: usize = 16;
+
+/// The S-box of SubBytes(), table 4
+pub const SBOX: [u8; 256] = build_sbox();
+
+/// The inverse S-box of InvSubBytes(), table 6
+pub const INV_SBOX: [u8; 256] = build_inv_sbox();
+
+/// The round constants' first bytes, the rest are zero
+const RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// The three key lengths AES is defined for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySize {
+    Aes128,
+    Aes192,
+    Aes256,
+}
+
+impl KeySize {
+    /// Picks the key size for a key of `key_len` bytes
+    ///
+    /// Returning None indicates AES has no key of that length
+    pub fn from_key_len(key_len: usize) -> Option<Self> {
+        match key_len {
+            16 => Some(Self::Aes128),
+            24 => Some(Self::Aes192),
+            32 => Some(Self::Aes256),
+            _ => None,
+        }
+    }
+
+    /// Key length in bytes
+    pub fn key_len(self) -> usize {
+        self.nk() * 4
+    }
+
+    /// Nk, key length in 32 bit words
+    pub fn nk(self) -> usize {
+        match self {
+            Self::Aes128 => 4,
+            Self::Aes192 => 6,
+            Self::Aes256 => 8,
+        }
+    }
+
+    /// Nr, the standard number of rounds
+    pub fn rounds(self) -> usize {
+        self.nk() + 6
+    }
+}
+
+/// A step of the cipher, reported to the trace hook with the state after it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// The input block, before anything is done to it
+    Input,
+    SubBytes,
+    ShiftRows,
+    MixColumns,
+    AddRoundKey,
+    InvSubBytes,
+    InvShiftRows,
+    InvMixColumns,
+}
+
+/// AES with an expanded key and any number of rounds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Aes {
+    key_size: KeySize,
+    round_keys: Vec<[u8; 16]>,
+}
+
+impl Aes {
+    /// Expands a 16, 24 or 32 byte key for the standard number of rounds
+    ///
+    /// Returning None indicates the key has the wrong length
+    pub fn new(key: &[u8]) -> Option<Self> {
+        let key_size = KeySize::from_key_len(key.len())?;
+        Self::with_rounds(key, key_size.rounds())
+    }
+
+    /// Expands a 16, 24 or 32 byte key for a reduced, or extended, number of rounds
+    ///
+    /// The last round never has MixColumns(), so `rounds` = 1 is just SubBytes(), ShiftRows() and two
+    /// AddRoundKey()s.
+    ///
+    /// Returning None indicates the key has the wrong length or there are no rounds
+    pub fn with_rounds(key: &[u8], rounds: usize) -> Option<Self> {
+        let key_size = KeySize::from_key_len(key.len())?;
+        if rounds == 0 {
+            return None;
+        }
+
+        Some(Self {
+            key_size,
+            round_keys: expand_key(key, rounds)?,
+        })
+    }
+
+    /// The size of the key the cipher was created with
+    pub fn key_size(&self) -> KeySize {
+        self.key_size
+    }
+
+    /// Number of rounds, Nr
+    pub fn rounds(&self) -> usize {
+        self.round_keys.len() - 1
+    }
+
+    /// The Nr + 1 round keys from KeyExpansion()
+    pub fn round_keys(&self) -> &[[u8; 16]] {
+        &self.round_keys
+    }
+
+    /// Encrypts a single block in place, Cipher()
+    pub fn encrypt_block(&self, block: &mut [u8; 16]) {
+        self.encrypt_block_traced(block, |_, _, _| {});
+    }
+
+    /// Decrypts a single block in place, InvCipher()
+    pub fn decrypt_block(&self, block: &mut [u8; 16]) {
+        self.decrypt_block_traced(block, |_, _, _| {});
+    }
+
+    /// Encrypts a single block in place, calling `trace` with the round and the state after every step
+    ///
+    /// The initial AddRoundKey() is round 0, as in appendix B
+    pub fn encrypt_block_traced<F>(&self, state: &mut [u8; 16], mut trace: F)
+    where
+        F: FnMut(usize, Step, &[u8; 16]),
+    {
+        let rounds = self.rounds();
+        trace(0, Step::Input, state);
+
+        add_round_key(state, &self.round_keys[0]);
+        trace(0, Step::AddRoundKey, state);
+
+        for round in 1..=rounds {
+            sub_bytes(state);
+            trace(round, Step::SubBytes, state);
+            shift_rows(state);
+            trace(round, Step::ShiftRows, state);
+            if round != rounds {
+                mix_columns(state);
+                trace(round, Step::MixColumns, state);
+            }
+            add_round_key(state, &self.round_keys[round]);
+            trace(round, Step::AddRoundKey, state);
+        }
+    }
+
+    /// Decrypts a single block in place, calling `trace` with the round and the state after every step
+    ///
+    /// Rounds count down, the first AddRoundKey() is round Nr and the last round is round 1, so each step is
+    /// reported with the same round as the encryption step it undoes
+    pub fn decrypt_block_traced<F>(&self, state: &mut [u8; 16], mut trace: F)
+    where
+        F: FnMut(usize, Step, &[u8; 16]),
+    {
+        let rounds = self.rounds();
+        trace(rounds, Step::Input, state);
+
+        for round in (1..=rounds).rev() {
+            add_round_key(state, &self.round_keys[round]);
+            trace(round, Step::AddRoundKey, state);
+            if round != rounds {
+                inv_mix_columns(state);
+                trace(round, Step::InvMixColumns, state);
+            }
+            inv_shift_rows(state);
+            trace(round, Step::InvShiftRows, state);
+            inv_sub_bytes(state);
+            trace(round, Step::InvSubBytes, state);
+        }
+
+        add_round_key(state, &self.round_keys[0]);
+        trace(0, Step::AddRoundKey, state);
+    }
+}
+
+/// KeyExpansion(), the `rounds + 1` round keys for a 16, 24 or 32 byte key
+///
+/// Returning None indicates the key has the wrong length
+pub fn expand_key(key: &[u8], rounds: usize) -> Option<Vec<[u8; 16]>> {
+    let nk = KeySize::from_key_len(key.len())?.nk();
+    let word_count = 4 * (rounds + 1);
+
+    let mut words: Vec<[u8; 4]> = key.chunks(4).map(|word| word.try_into().unwrap()).collect();
+    for i in nk..word_count {
+        let mut temp = words[i - 1];
+        if i % nk == 0 {
+            temp = sub_word(rot_word(temp));
+            // Extended round counts run past the table, carry on doubling in GF(2^8)
+            temp[0] ^= RCON
+                .get(i / nk - 1)
+                .copied()
+                .unwrap_or_else(|| xtime_pow(i / nk - 1));
+        } else if nk > 6 && i % nk == 4 {
+            temp = sub_word(temp);
+        }
+        let previous = words[i - nk];
+        words.push(std::array::from_fn(|j| previous[j] ^ temp[j]));
+    }
+
+    Some(
+        words
+            .chunks(4)
+            .map(|round_key| std::array::from_fn(|i| round_key[i / 4][i % 4]))
+            .collect(),
+    )
+}
+
+/// SubBytes(), every byte through the S-box
+pub fn sub_bytes(state: &mut [u8; 16]) {
+    for byte in state.iter_mut() {
+        *byte = SBOX[*byte as usize];
+    }
+}
+
+/// InvSubBytes(), every byte through the inverse S-box
+pub fn inv_sub_bytes(state: &mut [u8; 16]) {
+    for byte in state.iter_mut() {
+        *byte = INV_SBOX[*byte as usize];
+    }
+}
+
+/// ShiftRows(), row r is rotated left by r bytes
+pub fn shift_rows(state: &mut [u8; 16]) {
+    let input = *state;
+    for r in 0..4 {
+        for c in 0..4 {
+            state[r + 4 * c] = input[r + 4 * ((c + r) % 4)];
+        }
+    }
+}
+
+/// InvShiftRows(), row r is rotated right by r bytes
+pub fn inv_shift_rows(state: &mut [u8; 16]) {
+    let input = *state;
+    for r in 0..4 {
+        for c in 0..4 {
+            state[r + 4 * ((c + r) % 4)] = input[r + 4 * c];
+        }
+    }
+}
+
+/// MixColumns(), every column is multiplied by {03}x^3 + {01}x^2 + {01}x + {02}
+pub fn mix_columns(state: &mut [u8; 16]) {
+    for column in state.chunks_mut(4) {
+        let [s0, s1, s2, s3] = [column[0], column[1], column[2], column[3]];
+        column[0] = gf_mul(s0, 2) ^ gf_mul(s1, 3) ^ s2 ^ s3;
+        column[1] = s0 ^ gf_mul(s1, 2) ^ gf_mul(s2, 3) ^ s3;
+        column[2] = s0 ^ s1 ^ gf_mul(s2, 2) ^ gf_mul(s3, 3);
+        column[3] = gf_mul(s0, 3) ^ s1 ^ s2 ^ gf_mul(s3, 2);
+    }
+}
+
+/// InvMixColumns(), every column is multiplied by {0b}x^3 + {0d}x^2 + {09}x + {0e}
+pub fn inv_mix_columns(state: &mut [u8; 16]) {
+    for column in state.chunks_mut(4) {
+        let [s0, s1, s2, s3] = [column[0], column[1], column[2], column[3]];
+        column[0] = gf_mul(s0, 0x0e) ^ gf_mul(s1, 0x0b) ^ gf_mul(s2, 0x0d) ^ gf_mul(s3, 0x09);
+        column[1] = gf_mul(s0, 0x09) ^ gf_mul(s1, 0x0e) ^ gf_mul(s2, 0x0b) ^ gf_mul(s3, 0x0d);
+        column[2] = gf_mul(s0, 0x0d) ^ gf_mul(s1, 0x09) ^ gf_mul(s2, 0x0e) ^ gf_mul(s3, 0x0b);
+        column[3] = gf_mul(s0, 0x0b) ^ gf_mul(s1, 0x0d) ^ gf_mul(s2, 0x09) ^ gf_mul(s3, 0x0e);
+    }
+}
+
+/// AddRoundKey(), XORs the round key into the state
+pub fn add_round_key(state: &mut [u8; 16], round_key: &[u8; 16]) {
+    for (byte, key_byte) in state.iter_mut().zip(round_key) {
+        *byte ^= key_byte;
+    }
+}
+
+/// Multiplication in GF(2^8) modulo x^8 + x^4 + x^3 + x + 1
+pub const fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        a = xtime(a);
+        b >>= 1;
+    }
+    product
+}
+
+/// xtime(), multiplication by x in GF(2^8)
+pub const fn xtime(a: u8) -> u8 {
+    (a << 1) ^ if a & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+/// x^n in GF(2^8)
+fn xtime_pow(n: usize) -> u8 {
+    (0..n).fold(1, |a, _| xtime(a))
+}
+
+fn rot_word(word: [u8; 4]) -> [u8; 4] {
+    [word[1], word[2], word[3], word[0]]
+}
+
+fn sub_word(word: [u8; 4]) -> [u8; 4] {
+    word.map(|byte| SBOX[byte as usize])
+}
+
+/// The multiplicative inverse in GF(2^8) followed by the affine transformation of equation 5.2
+const fn build_sbox() -> [u8; 256] {
+    let mut sbox = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        // b^254 is the inverse of b, and maps 0 to 0
+        let b = i as u8;
+        let mut inverse = 1;
+        let mut exponent = 0;
+        while exponent < 254 {
+            inverse = gf_mul(inverse, b);
+            exponent += 1;
+        }
+
+        sbox[i] = inverse
+            ^ inverse.rotate_left(1)
+            ^ inverse.rotate_left(2)
+            ^ inverse.rotate_left(3)
+            ^ inverse.rotate_left(4)
+            ^ 0x63;
+        i += 1;
+    }
+    sbox
+}
+
+const fn build_inv_sbox() -> [u8; 256] {
+    let mut inv_sbox = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        inv_sbox[SBOX[i] as usize] = i as u8;
+        i += 1;
+    }
+    inv_sbox
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::{
+        cipher::{consts::U16, generic_array::GenericArray, BlockEncrypt, KeyInit},
+        Aes128, Aes192, Aes256,
+    };
+    use rand::Rng;
+
+    use super::*;
+    use crate::set_1::hex_to_bytes;
+
+    fn block(hex: &str) -> [u8; 16] {
+        hex_to_bytes(hex).unwrap().try_into().unwrap()
+    }
+
+    #[test]
+    fn sbox() {
+        assert_eq!(0x63, SBOX[0x00]);
+        assert_eq!(0xed, SBOX[0x53]);
+        assert_eq!(0x16, SBOX[0xff]);
+        assert_eq!(0x53, INV_SBOX[0xed]);
+    }
+
+    #[test]
+    fn key_expansion_appendix_a() {
+        let key = hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c").unwrap();
+        let round_keys = expand_key(&key, 10).unwrap();
+        assert_eq!(11, round_keys.len());
+        assert_eq!(block("a0fafe1788542cb123a339392a6c7605"), round_keys[1]);
+        assert_eq!(block("d014f9a8c9ee2589e13f0cc8b6630ca6"), round_keys[10]);
+
+        let key = hex_to_bytes("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+            .unwrap();
+        let round_keys = expand_key(&key, 14).unwrap();
+        assert_eq!(block("fe4890d1e6188d0b046df344706c631e"), round_keys[14]);
+    }
+
+    #[test]
+    fn cipher_trace_appendix_b() {
+        let aes = Aes::new(&hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c").unwrap()).unwrap();
+        let mut state = block("3243f6a8885a308d313198a2e0370734");
+
+        let mut trace = Vec::new();
+        aes.encrypt_block_traced(&mut state, |round, step, state| {
+            trace.push((round, step, *state))
+        });
+
+        assert_eq!(block("3925841d02dc09fbdc118597196a0b32"), state);
+        assert_eq!(
+            (
+                0,
+                Step::AddRoundKey,
+                block("193de3bea0f4e22b9ac68d2ae9f84808")
+            ),
+            trace[1]
+        );
+        assert_eq!(
+            (1, Step::SubBytes, block("d42711aee0bf98f1b8b45de51e415230")),
+            trace[2]
+        );
+        assert_eq!(
+            (
+                1,
+                Step::ShiftRows,
+                block("d4bf5d30e0b452aeb84111f11e2798e5")
+            ),
+            trace[3]
+        );
+        assert_eq!(
+            (
+                1,
+                Step::MixColumns,
+                block("046681e5e0cb199a48f8d37a2806264c")
+            ),
+            trace[4]
+        );
+        // The last round has no MixColumns()
+        assert_eq!(2 + 9 * 4 + 3, trace.len());
+    }
+
+    #[test]
+    fn example_vectors_appendix_c() {
+        let plain_text = block("00112233445566778899aabbccddeeff");
+        let vectors = [
+            (
+                "000102030405060708090a0b0c0d0e0f",
+                "69c4e0d86a7b0430d8cdb78070b4c55a",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f1011121314151617",
+                "dda97ca4864cdfe06eaf70a0ec0d7191",
+            ),
+            (
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+                "8ea2b7ca516745bfeafc49904b496089",
+            ),
+        ];
+
+        for (key, cipher_text) in vectors {
+            let aes = Aes::new(&hex_to_bytes(key).unwrap()).unwrap();
+            let mut state = plain_text;
+            aes.encrypt_block(&mut state);
+            assert_eq!(block(cipher_text), state);
+
+            aes.decrypt_block(&mut state);
+            assert_eq!(plain_text, state);
+        }
+    }
+
+    #[test]
+    fn matches_aes_crate() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..64 {
+            let key: [u8; 32] = rng.gen();
+            let plain_text: [u8; 16] = rng.gen();
+
+            let expected = [
+                encrypt_with(Aes128::new_from_slice(&key[..16]).unwrap(), plain_text),
+                encrypt_with(Aes192::new_from_slice(&key[..24]).unwrap(), plain_text),
+                encrypt_with(Aes256::new_from_slice(&key).unwrap(), plain_text),
+            ];
+            for (key_len, expected) in [16, 24, 32].into_iter().zip(expected) {
+                let mut state = plain_text;
+                Aes::new(&key[..key_len]).unwrap().encrypt_block(&mut state);
+                assert_eq!(expected, state);
+            }
+        }
+    }
+
+    fn encrypt_with<C: BlockEncrypt<BlockSize = U16>>(cipher: C, plain_text: [u8; 16]) -> [u8; 16] {
+        let mut block = GenericArray::from(plain_text);
+        cipher.encrypt_block(&mut block);
+        block.into()
+    }
+
+    #[test]
+    fn reduced_rounds() {
+        let key = [7; 16];
+        assert_eq!(None, Aes::with_rounds(&key, 0));
+        assert_eq!(None, Aes::new(&[0; 20]));
+
+        for rounds in [1, 4, 10, 16] {
+            let aes = Aes::with_rounds(&key, rounds).unwrap();
+            assert_eq!(rounds, aes.rounds());
+
+            let plain_text = *b"YELLOW SUBMARINE";
+            let mut state = plain_text;
+            aes.encrypt_block(&mut state);
+            assert_ne!(plain_text, state);
+            aes.decrypt_block(&mut state);
+            assert_eq!(plain_text, state);
+        }
+
+        // A single round is SubBytes(), ShiftRows() and the two round keys
+        let aes = Aes::with_rounds(&key, 1).unwrap();
+        let mut expected = *b"YELLOW SUBMARINE";
+        add_round_key(&mut expected, &aes.round_keys()[0]);
+        sub_bytes(&mut expected);
+        shift_rows(&mut expected);
+        add_round_key(&mut expected, &aes.round_keys()[1]);
+
+        let mut state = *b"YELLOW SUBMARINE";
+        aes.encrypt_block(&mut state);
+        assert_eq!(expected, state);
+    }
+}
@@ -0,0 +1,275 @@
+//! GF(2^128), the field GHASH multiplies in
+//!
+//! Elements are polynomials over GF(2) modulo x^128 + x^7 + x^2 + x + 1, in GCM's bit order: the first bit of the
+//! 16 big endian bytes, the most significant bit of the u128, is the coefficient of x^0.
+
+use std::ops::{Add, AddAssign, Mul, MulAssign, Sub, SubAssign};
+
+use num_bigint::BigUint;
+use num_traits::One;
+use rand::Rng;
+
+use crate::{gf2::Gf2Matrix, polynomial::Field};
+
+/// A polynomial with coefficients in GF(2^128)
+pub type Polynomial = crate::polynomial::Polynomial<Gf128>;
+
+/// x^7 + x^2 + x + 1 in GCM's bit order, what x^128 reduces to
+const R: u128 = 0xe1 << 120;
+
+/// An element of GF(2^128)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Gf128(pub u128);
+
+impl Gf128 {
+    pub const ZERO: Gf128 = Gf128(0);
+    pub const ONE: Gf128 = Gf128(1 << 127);
+
+    pub fn from_bytes(bytes: &[u8; 16]) -> Self {
+        Gf128(u128::from_be_bytes(*bytes))
+    }
+
+    /// Reads up to 16 bytes, zero padding a short block as GHASH does
+    pub fn from_partial_block(bytes: &[u8]) -> Self {
+        let mut block = [0; 16];
+        block[..bytes.len()].copy_from_slice(bytes);
+        Self::from_bytes(&block)
+    }
+
+    pub fn to_bytes(self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    /// The element x^i
+    pub fn x_pow(i: u32) -> Self {
+        Gf128::ONE.mul_x_pow(i)
+    }
+
+    /// Multiplies by x^i, one shift and reduction per power
+    fn mul_x_pow(mut self, i: u32) -> Self {
+        for _ in 0..i {
+            let carry = self.0 & 1;
+            self.0 >>= 1;
+            if carry == 1 {
+                self.0 ^= R;
+            }
+        }
+        self
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn pow(self, mut exponent: u128) -> Self {
+        let mut base = self;
+        let mut result = Gf128::ONE;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result *= base;
+            }
+            base *= base;
+            exponent >>= 1;
+        }
+        result
+    }
+
+    /// The multiplicative inverse, a^(2^128 - 2)
+    ///
+    /// Returning None indicates the element is zero
+    pub fn inverse(self) -> Option<Self> {
+        if self.is_zero() {
+            return None;
+        }
+        Some(self.pow(u128::MAX - 1))
+    }
+
+    /// The coefficient of x^i
+    pub fn coefficient(self, i: usize) -> bool {
+        (self.0 >> (127 - i)) & 1 == 1
+    }
+
+    /// Creates an element from its coefficients, x^0 first
+    pub fn from_coefficients<I: IntoIterator<Item = bool>>(coefficients: I) -> Self {
+        Gf128(
+            coefficients
+                .into_iter()
+                .take(128)
+                .enumerate()
+                .fold(0, |value, (i, c)| value | (c as u128) << (127 - i)),
+        )
+    }
+
+    /// The matrix of the linear map that takes a column of coefficients to those of a times it
+    pub fn multiplication_matrix(self) -> Gf2Matrix {
+        let mut matrix = Gf2Matrix::new(128, 128);
+        let mut column = self;
+        for col in 0..128 {
+            for row in 0..128 {
+                matrix.set(row, col, column.coefficient(row));
+            }
+            column = column.mul_x_pow(1);
+        }
+        matrix
+    }
+
+    /// The matrix of squaring, which is linear in characteristic 2 as the cross terms cancel
+    pub fn squaring_matrix() -> Gf2Matrix {
+        let mut matrix = Gf2Matrix::new(128, 128);
+        for col in 0..128 {
+            let square = Gf128::x_pow(2 * col as u32);
+            for row in 0..128 {
+                matrix.set(row, col, square.coefficient(row));
+            }
+        }
+        matrix
+    }
+
+    /// The square root, a^(2^127), squaring is linear and invertible in characteristic 2
+    pub fn sqrt(self) -> Self {
+        self.pow(1 << 127)
+    }
+}
+
+impl Field for Gf128 {
+    fn zero() -> Self {
+        Gf128::ZERO
+    }
+
+    fn one() -> Self {
+        Gf128::ONE
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        Gf128::inverse(*self)
+    }
+
+    fn order() -> BigUint {
+        BigUint::one() << 128u32
+    }
+
+    fn characteristic() -> BigUint {
+        BigUint::from(2u32)
+    }
+
+    fn from_integer(n: usize) -> Self {
+        if n % 2 == 1 {
+            Gf128::ONE
+        } else {
+            Gf128::ZERO
+        }
+    }
+
+    fn pth_root(&self) -> Self {
+        self.sqrt()
+    }
+
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        Gf128(rng.gen())
+    }
+}
+
+/// Addition of polynomials over GF(2) is XOR of their coefficients
+impl Add for Gf128 {
+    type Output = Gf128;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, rhs: Gf128) -> Gf128 {
+        Gf128(self.0 ^ rhs.0)
+    }
+}
+
+impl AddAssign for Gf128 {
+    #[allow(clippy::suspicious_op_assign_impl)]
+    fn add_assign(&mut self, rhs: Gf128) {
+        self.0 ^= rhs.0;
+    }
+}
+
+/// Subtraction is addition in characteristic 2
+impl Sub for Gf128 {
+    type Output = Gf128;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn sub(self, rhs: Gf128) -> Gf128 {
+        self + rhs
+    }
+}
+
+impl SubAssign for Gf128 {
+    #[allow(clippy::suspicious_op_assign_impl)]
+    fn sub_assign(&mut self, rhs: Gf128) {
+        *self += rhs;
+    }
+}
+
+/// Algorithm 1 of NIST SP 800-38D, shift and add
+impl Mul for Gf128 {
+    type Output = Gf128;
+
+    fn mul(self, rhs: Gf128) -> Gf128 {
+        let mut product = 0;
+        let mut shifted = rhs.0;
+        for i in 0..128 {
+            if (self.0 >> (127 - i)) & 1 == 1 {
+                product ^= shifted;
+            }
+            let carry = shifted & 1;
+            shifted >>= 1;
+            if carry == 1 {
+                shifted ^= R;
+            }
+        }
+        Gf128(product)
+    }
+}
+
+impl MulAssign for Gf128 {
+    fn mul_assign(&mut self, rhs: Gf128) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn field_laws() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..20 {
+            let [a, b, c] = [Gf128(rng.gen()), Gf128(rng.gen()), Gf128(rng.gen())];
+
+            assert_eq!(a * b, b * a);
+            assert_eq!(a * (b + c), a * b + a * c);
+            assert_eq!((a * b) * c, a * (b * c));
+            assert_eq!(a, a * Gf128::ONE);
+            assert_eq!(Gf128::ONE, a * a.inverse().unwrap());
+            assert_eq!(a, (a * a).sqrt());
+        }
+        assert_eq!(None, Gf128::ZERO.inverse());
+    }
+
+    #[test]
+    fn reduction() {
+        // x^127 * x = x^128 = x^7 + x^2 + x + 1
+        assert_eq!(Gf128(1), Gf128::x_pow(127));
+        assert_eq!(Gf128(R), Gf128::x_pow(127) * Gf128::x_pow(1));
+        assert_eq!(Gf128::x_pow(5), Gf128::x_pow(2) * Gf128::x_pow(3));
+    }
+
+    #[test]
+    fn matrices() {
+        let to_column = |a: Gf128| Gf2Matrix::from_fn(128, 1, |row, _| a.coefficient(row));
+        let [a, b] = [Gf128(rand::random()), Gf128(rand::random())];
+
+        assert_eq!(
+            a,
+            Gf128::from_coefficients((0..128).map(|i| a.coefficient(i)))
+        );
+        assert_eq!(to_column(a * b), &a.multiplication_matrix() * &to_column(b));
+        assert_eq!(to_column(a * a), &Gf128::squaring_matrix() * &to_column(a));
+    }
+}
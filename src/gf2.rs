@@ -0,0 +1,229 @@
+//! Linear algebra over GF(2), where addition is XOR
+
+use std::ops::{Add, Mul};
+
+/// A matrix of bits, each row packed into u64 words with column c at bit c % 64 of word c / 64
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Gf2Matrix {
+    rows: usize,
+    cols: usize,
+    words: usize,
+    data: Vec<u64>,
+}
+
+impl Gf2Matrix {
+    /// The zero matrix
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let words = cols.div_ceil(64);
+        Self {
+            rows,
+            cols,
+            words,
+            data: vec![0; rows * words],
+        }
+    }
+
+    pub fn identity(size: usize) -> Self {
+        let mut identity = Self::new(size, size);
+        for i in 0..size {
+            identity.set(i, i, true);
+        }
+        identity
+    }
+
+    /// Creates a matrix from the value of every entry
+    pub fn from_fn<F: FnMut(usize, usize) -> bool>(rows: usize, cols: usize, mut f: F) -> Self {
+        let mut matrix = Self::new(rows, cols);
+        for row in 0..rows {
+            for col in 0..cols {
+                matrix.set(row, col, f(row, col));
+            }
+        }
+        matrix
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        assert!(row < self.rows && col < self.cols);
+        (self.data[row * self.words + col / 64] >> (col % 64)) & 1 == 1
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        assert!(row < self.rows && col < self.cols);
+        let word = &mut self.data[row * self.words + col / 64];
+        let bit = 1 << (col % 64);
+        if value {
+            *word |= bit;
+        } else {
+            *word &= !bit;
+        }
+    }
+
+    fn row(&self, row: usize) -> &[u64] {
+        &self.data[row * self.words..(row + 1) * self.words]
+    }
+
+    /// Adds row `src` to row `dst`
+    fn add_row(&mut self, src: usize, dst: usize) {
+        for i in 0..self.words {
+            self.data[dst * self.words + i] ^= self.data[src * self.words + i];
+        }
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for i in 0..self.words {
+            self.data.swap(a * self.words + i, b * self.words + i);
+        }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut transpose = Self::new(self.cols, self.rows);
+        for row in 0..self.rows {
+            for col in set_bits(self.row(row)) {
+                transpose.set(col, row, true);
+            }
+        }
+        transpose
+    }
+
+    /// Gaussian elimination to reduced row echelon form, returning the pivot column of each non-zero row
+    pub fn row_reduce(&mut self) -> Vec<usize> {
+        let mut pivots = Vec::new();
+        for col in 0..self.cols {
+            let row = pivots.len();
+            let Some(pivot) = (row..self.rows).find(|&r| self.get(r, col)) else {
+                continue;
+            };
+            self.swap_rows(pivot, row);
+            for other in 0..self.rows {
+                if other != row && self.get(other, col) {
+                    self.add_row(row, other);
+                }
+            }
+            pivots.push(col);
+        }
+        pivots
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce().len()
+    }
+
+    /// A basis of the vectors v with Mv = 0, one per row
+    ///
+    /// Every free column of the reduced matrix gives a basis vector with a one there, and the pivot columns set to
+    /// cancel it in their rows.
+    pub fn kernel(&self) -> Self {
+        let mut reduced = self.clone();
+        let pivots = reduced.row_reduce();
+        let free: Vec<usize> = (0..self.cols).filter(|col| !pivots.contains(col)).collect();
+
+        let mut kernel = Self::new(free.len(), self.cols);
+        for (i, &col) in free.iter().enumerate() {
+            kernel.set(i, col, true);
+            for (row, &pivot) in pivots.iter().enumerate() {
+                if reduced.get(row, col) {
+                    kernel.set(i, pivot, true);
+                }
+            }
+        }
+        kernel
+    }
+}
+
+/// The indices of the set bits, lowest first
+fn set_bits(words: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    words.iter().enumerate().flat_map(|(i, &word)| {
+        let mut word = word;
+        std::iter::from_fn(move || {
+            if word == 0 {
+                return None;
+            }
+            let bit = word.trailing_zeros() as usize;
+            word &= word - 1;
+            Some(i * 64 + bit)
+        })
+    })
+}
+
+impl Add for &Gf2Matrix {
+    type Output = Gf2Matrix;
+
+    fn add(self, rhs: &Gf2Matrix) -> Gf2Matrix {
+        assert!(self.rows == rhs.rows && self.cols == rhs.cols);
+        let mut sum = self.clone();
+        for (s, r) in sum.data.iter_mut().zip(&rhs.data) {
+            *s ^= r;
+        }
+        sum
+    }
+}
+
+/// Each row of the product is the sum of the rows of the right hand side picked out by a row of the left, so sparse
+/// matrices multiply quickly
+impl Mul for &Gf2Matrix {
+    type Output = Gf2Matrix;
+
+    fn mul(self, rhs: &Gf2Matrix) -> Gf2Matrix {
+        assert_eq!(self.cols, rhs.rows);
+        let mut product = Gf2Matrix::new(self.rows, rhs.cols);
+        for row in 0..self.rows {
+            let product_row = &mut product.data[row * rhs.words..(row + 1) * rhs.words];
+            for col in set_bits(self.row(row)) {
+                for (p, r) in product_row.iter_mut().zip(rhs.row(col)) {
+                    *p ^= r;
+                }
+            }
+        }
+        product
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    fn random(rows: usize, cols: usize) -> Gf2Matrix {
+        let mut rng = rand::thread_rng();
+        Gf2Matrix::from_fn(rows, cols, |_, _| rng.gen())
+    }
+
+    #[test]
+    fn products() {
+        let a = random(70, 130);
+        let b = random(130, 65);
+        let c = random(130, 65);
+
+        assert_eq!(a, &a * &Gf2Matrix::identity(130));
+        assert_eq!(&a * &(&b + &c), &(&a * &b) + &(&a * &c));
+        assert_eq!((&a * &b).transpose(), &b.transpose() * &a.transpose());
+
+        let product = &a * &b;
+        for (row, col) in [(0, 0), (69, 64), (35, 17)] {
+            let expected = (0..130).fold(false, |sum, i| sum ^ (a.get(row, i) & b.get(i, col)));
+            assert_eq!(expected, product.get(row, col));
+        }
+    }
+
+    #[test]
+    fn kernel() {
+        // More columns than rows always leaves a kernel
+        let a = random(100, 150);
+        let kernel = a.kernel();
+        assert_eq!(150, a.rank() + kernel.rows());
+        assert_eq!(kernel.rows(), kernel.rank());
+        assert_eq!(Gf2Matrix::new(100, kernel.rows()), &a * &kernel.transpose());
+
+        assert_eq!(0, Gf2Matrix::identity(10).kernel().rows());
+        assert_eq!(Gf2Matrix::identity(10), Gf2Matrix::new(3, 10).kernel());
+    }
+}
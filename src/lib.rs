@@ -1,2 +1,16 @@
+pub mod block_cipher;
+pub mod chacha20_poly1305;
+pub mod fips_197;
+pub mod gf128;
+pub mod gf2;
+pub mod modes;
+pub mod padding;
+pub mod polynomial;
 pub mod set_1;
 pub mod set_2;
+pub mod set_3;
+pub mod set_5;
+pub mod set_6;
+pub mod set_7;
+pub mod set_8;
+pub mod stream;
@@ -0,0 +1,40 @@
+//! Block cipher modes of operation, generic over any [BlockCipher](crate::block_cipher::BlockCipher)
+
+mod cbc;
+mod cbc_cs;
+mod cfb;
+mod ctr;
+mod ecb;
+mod gcm;
+mod ofb;
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(test)]
+mod sp_800_38a;
+mod xts;
+
+pub use cbc::{
+    cbc_decrypt, cbc_decrypt_in_place, cbc_decrypt_padded, cbc_encrypt, cbc_encrypt_in_place,
+    cbc_encrypt_padded,
+};
+pub use cbc_cs::{cbc_cs_decrypt, cbc_cs_encrypt, CiphertextStealing};
+pub use cfb::{
+    cfb128_decrypt, cfb128_encrypt, cfb1_decrypt, cfb1_encrypt, cfb8_decrypt, cfb8_encrypt,
+};
+pub use ctr::{ctr_apply, ctr_apply_keystream, ctr_apply_with_counter, ctr_apply_with_counter_len};
+pub use ecb::{
+    ecb_decrypt, ecb_decrypt_in_place, ecb_decrypt_padded, ecb_encrypt, ecb_encrypt_in_place,
+    ecb_encrypt_padded,
+};
+pub(crate) use gcm::ghash_lengths_block;
+pub use gcm::{gcm_hash_key, gcm_open, gcm_seal, ghash};
+pub use ofb::ofb_apply;
+#[cfg(feature = "parallel")]
+pub use parallel::{
+    cbc_decrypt_in_place_parallel, ctr_apply_keystream_parallel, ecb_decrypt_in_place_parallel,
+    ecb_encrypt_in_place_parallel, DEFAULT_PARALLEL_THRESHOLD,
+};
+pub use xts::{
+    xts_aes_128_decrypt, xts_aes_128_encrypt, xts_aes_256_decrypt, xts_aes_256_encrypt,
+    xts_decrypt, xts_encrypt,
+};
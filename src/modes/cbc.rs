@@ -0,0 +1,204 @@
+//! Cipher block chaining mode
+
+use crate::{block_cipher::BlockCipher, padding::Padding};
+
+/// Encrypts every block of the buffer in place, each block is XORed with the previous cipher text block, or the IV,
+/// first
+///
+/// Returning None indicates the IV is not a single block or the buffer is not a whole number of blocks
+pub fn cbc_encrypt_in_place<'a, C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    buffer: &'a mut [u8],
+) -> Option<&'a mut [u8]> {
+    if iv.len() != C::BLOCK_SIZE || !buffer.len().is_multiple_of(C::BLOCK_SIZE) {
+        return None;
+    }
+
+    let mut previous_block = iv;
+    for block in buffer.chunks_exact_mut(C::BLOCK_SIZE) {
+        for (byte, previous_byte) in block.iter_mut().zip(previous_block) {
+            *byte ^= previous_byte;
+        }
+        cipher.encrypt_block(block);
+        previous_block = block;
+    }
+
+    Some(buffer)
+}
+
+/// Decrypts every block of the buffer in place
+///
+/// Returning None indicates the IV is not a single block or the buffer is not a whole number of blocks
+pub fn cbc_decrypt_in_place<'a, C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    buffer: &'a mut [u8],
+) -> Option<&'a mut [u8]> {
+    if iv.len() != C::BLOCK_SIZE || !buffer.len().is_multiple_of(C::BLOCK_SIZE) {
+        return None;
+    }
+
+    // The cipher text block is overwritten by its plain text, so it's kept around for the next block
+    let mut previous_block = iv.to_vec();
+    let mut cipher_text_block = vec![0; C::BLOCK_SIZE];
+    for block in buffer.chunks_exact_mut(C::BLOCK_SIZE) {
+        cipher_text_block.copy_from_slice(block);
+        cipher.decrypt_block(block);
+        for (byte, previous_byte) in block.iter_mut().zip(&previous_block) {
+            *byte ^= previous_byte;
+        }
+        std::mem::swap(&mut previous_block, &mut cipher_text_block);
+    }
+
+    Some(buffer)
+}
+
+/// Encrypts a whole number of blocks
+///
+/// Returning None indicates the IV is not a single block or the plain text is not a whole number of blocks
+pub fn cbc_encrypt<C: BlockCipher>(cipher: &C, iv: &[u8], plain_text: &[u8]) -> Option<Vec<u8>> {
+    let mut cipher_text = plain_text.to_vec();
+    cbc_encrypt_in_place(cipher, iv, &mut cipher_text)?;
+    Some(cipher_text)
+}
+
+/// Decrypts a whole number of blocks
+///
+/// Returning None indicates the IV is not a single block or the cipher text is not a whole number of blocks
+pub fn cbc_decrypt<C: BlockCipher>(cipher: &C, iv: &[u8], cipher_text: &[u8]) -> Option<Vec<u8>> {
+    let mut plain_text = cipher_text.to_vec();
+    cbc_decrypt_in_place(cipher, iv, &mut plain_text)?;
+    Some(plain_text)
+}
+
+/// Pads the plain text and encrypts it
+///
+/// Returning None indicates the IV is not a single block or the padding can't fill out the last block
+pub fn cbc_encrypt_padded<C: BlockCipher, P: Padding>(
+    cipher: &C,
+    iv: &[u8],
+    plain_text: &[u8],
+    padding: &P,
+) -> Option<Vec<u8>> {
+    let mut cipher_text = padding.pad(plain_text, C::BLOCK_SIZE)?;
+    cbc_encrypt_in_place(cipher, iv, &mut cipher_text)?;
+    Some(cipher_text)
+}
+
+/// Decrypts the cipher text and removes its padding
+///
+/// Returning None indicates the IV is not a single block, the cipher text is not a whole number of blocks or the
+/// padding is invalid
+pub fn cbc_decrypt_padded<C: BlockCipher, P: Padding>(
+    cipher: &C,
+    iv: &[u8],
+    cipher_text: &[u8],
+    padding: &P,
+) -> Option<Vec<u8>> {
+    let mut plain_text = cbc_decrypt(cipher, iv, cipher_text)?;
+    let unpadded_len = padding.unpadded_len(&plain_text, C::BLOCK_SIZE)?;
+    plain_text.truncate(unpadded_len);
+    Some(plain_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::{Aes192, Aes256};
+
+    use super::*;
+    use crate::{
+        block_cipher::tests::ToyCipher,
+        fips_197,
+        padding::{AnsiX923, Iso7816_4, NoPadding, Pkcs7},
+        set_1::hex_to_bytes,
+    };
+
+    const IV: &str = "000102030405060708090a0b0c0d0e0f";
+    const PLAIN_TEXT: &str = "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51";
+
+    #[test]
+    fn sp_800_38a_vectors() {
+        let iv = hex_to_bytes(IV).unwrap();
+        let plain_text = hex_to_bytes(PLAIN_TEXT).unwrap();
+
+        let key = hex_to_bytes("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b").unwrap();
+        let expected =
+            hex_to_bytes("4f021db243bc633d7178183a9fa071e8b4d9ada9ad7dedf4e5e738763f69145a")
+                .unwrap();
+        let cipher = Aes192::new(&key.clone().try_into().unwrap());
+        assert_eq!(
+            Some(expected.clone()),
+            cbc_encrypt(&cipher, &iv, &plain_text)
+        );
+        assert_eq!(
+            Some(plain_text.clone()),
+            cbc_decrypt(&cipher, &iv, &expected)
+        );
+        let cipher = <fips_197::Aes as BlockCipher>::new(&key);
+        assert_eq!(Some(expected), cbc_encrypt(&cipher, &iv, &plain_text));
+
+        let key = hex_to_bytes("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+            .unwrap();
+        let expected =
+            hex_to_bytes("f58c4c04d6e5f1ba779eabfb5f7bfbd69cfc4e967edb808d679f777bc6702c7d")
+                .unwrap();
+        let cipher = Aes256::new(&key.try_into().unwrap());
+        assert_eq!(
+            Some(expected.clone()),
+            cbc_encrypt(&cipher, &iv, &plain_text)
+        );
+        assert_eq!(Some(plain_text), cbc_decrypt(&cipher, &iv, &expected));
+    }
+
+    #[test]
+    fn in_place() {
+        let cipher = ToyCipher::new(b"toy key!");
+        let iv = b"toy iv!!";
+        let plain_text = (0..64).collect::<Vec<u8>>();
+
+        let mut buffer = plain_text.clone();
+        cbc_encrypt_in_place(&cipher, iv, &mut buffer).unwrap();
+        assert_eq!(cbc_encrypt(&cipher, iv, &plain_text).unwrap(), buffer);
+        cbc_decrypt_in_place(&cipher, iv, &mut buffer).unwrap();
+        assert_eq!(plain_text, buffer);
+
+        assert_eq!(None, cbc_decrypt_in_place(&cipher, iv, &mut buffer[1..]));
+    }
+
+    #[test]
+    fn padded_toy_cipher() {
+        let cipher = ToyCipher::new(b"toy key!");
+        let iv = b"toy iv!!";
+
+        for len in [0, 1, 7, 8, 9, 30] {
+            let plain_text = vec![b'A'; len];
+            let cipher_text = cbc_encrypt_padded(&cipher, iv, &plain_text, &Pkcs7).unwrap();
+            assert_eq!((len / 8 + 1) * 8, cipher_text.len());
+            assert_eq!(
+                Some(plain_text.clone()),
+                cbc_decrypt_padded(&cipher, iv, &cipher_text, &Pkcs7)
+            );
+
+            let cipher_text = cbc_encrypt_padded(&cipher, iv, &plain_text, &Iso7816_4).unwrap();
+            assert_eq!(
+                Some(plain_text),
+                cbc_decrypt_padded(&cipher, iv, &cipher_text, &Iso7816_4)
+            );
+        }
+
+        // An unpadded block of zeros decrypts to invalid padding
+        let cipher_text = cbc_encrypt(&cipher, iv, &[0; 8]).unwrap();
+        assert_eq!(None, cbc_decrypt_padded(&cipher, iv, &cipher_text, &Pkcs7));
+        assert_eq!(
+            None,
+            cbc_decrypt_padded(&cipher, iv, &cipher_text, &AnsiX923)
+        );
+        assert_eq!(
+            Some(vec![0; 8]),
+            cbc_decrypt_padded(&cipher, iv, &cipher_text, &NoPadding)
+        );
+        assert_eq!(None, cbc_encrypt_padded(&cipher, iv, &[0; 7], &NoPadding));
+        assert_eq!(None, cbc_encrypt(&cipher, &[0; 16], &[0; 8]));
+    }
+}
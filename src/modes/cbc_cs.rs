@@ -0,0 +1,213 @@
+//! CBC with ciphertext stealing, from the addendum to NIST SP 800-38A
+//!
+//! The last block is zero padded and encrypted as usual, then the padding's worth of cipher text is dropped from the
+//! block before it, as decryption can recover it from the last block. The three variants only differ in the order of
+//! the last two blocks.
+
+use super::cbc::{cbc_decrypt_in_place, cbc_encrypt_in_place};
+use crate::block_cipher::BlockCipher;
+
+/// Order of the last two cipher text blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiphertextStealing {
+    /// The partial block comes before the last whole block
+    Cs1,
+    /// The partial block comes last, unless the plain text is a whole number of blocks, making it plain CBC then
+    Cs2,
+    /// The partial block always comes last, Kerberos' AES-CTS
+    Cs3,
+}
+
+impl CiphertextStealing {
+    /// Whether the last two blocks are swapped from CS1's order, for the length of the last plain text block
+    fn swaps(&self, last_block_len: usize, block_size: usize) -> bool {
+        match self {
+            CiphertextStealing::Cs1 => false,
+            CiphertextStealing::Cs2 => last_block_len != block_size,
+            CiphertextStealing::Cs3 => true,
+        }
+    }
+}
+
+/// Number of blocks and length of the last, possibly partial, block
+fn block_lens(text_len: usize, block_size: usize) -> (usize, usize) {
+    let blocks = text_len.div_ceil(block_size);
+    (blocks, text_len - (blocks - 1) * block_size)
+}
+
+/// Encrypts plain text of at least one block into cipher text of the same length
+///
+/// Returning None indicates the IV is not a single block or the plain text is shorter than a block
+pub fn cbc_cs_encrypt<C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    plain_text: &[u8],
+    variant: CiphertextStealing,
+) -> Option<Vec<u8>> {
+    if plain_text.len() < C::BLOCK_SIZE {
+        return None;
+    }
+    let (blocks, last_block_len) = block_lens(plain_text.len(), C::BLOCK_SIZE);
+
+    let mut cipher_text = plain_text.to_vec();
+    cipher_text.resize(blocks * C::BLOCK_SIZE, 0);
+    cbc_encrypt_in_place(cipher, iv, &mut cipher_text)?;
+    if blocks == 1 {
+        return Some(cipher_text);
+    }
+
+    // Steal the end of the second to last block
+    let second_to_last = (blocks - 2) * C::BLOCK_SIZE;
+    cipher_text.drain((second_to_last + last_block_len)..(second_to_last + C::BLOCK_SIZE));
+    if variant.swaps(last_block_len, C::BLOCK_SIZE) {
+        cipher_text[second_to_last..].rotate_left(last_block_len);
+    }
+
+    Some(cipher_text)
+}
+
+/// Decrypts cipher text of at least one block into plain text of the same length
+///
+/// Returning None indicates the IV is not a single block or the cipher text is shorter than a block
+pub fn cbc_cs_decrypt<C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    cipher_text: &[u8],
+    variant: CiphertextStealing,
+) -> Option<Vec<u8>> {
+    if cipher_text.len() < C::BLOCK_SIZE || iv.len() != C::BLOCK_SIZE {
+        return None;
+    }
+    let (blocks, last_block_len) = block_lens(cipher_text.len(), C::BLOCK_SIZE);
+
+    let mut plain_text = cipher_text.to_vec();
+    if blocks == 1 {
+        cbc_decrypt_in_place(cipher, iv, &mut plain_text)?;
+        return Some(plain_text);
+    }
+
+    // Back to CS1's order, the partial block then the last whole block
+    let second_to_last = (blocks - 2) * C::BLOCK_SIZE;
+    if variant.swaps(last_block_len, C::BLOCK_SIZE) {
+        plain_text[second_to_last..].rotate_right(last_block_len);
+    }
+
+    // Decrypting the last block gives the stolen cipher text, XORed with the zero padding, and the last plain text
+    let mut last_block = plain_text[(second_to_last + last_block_len)..].to_vec();
+    cipher.decrypt_block(&mut last_block);
+    plain_text.splice(
+        (second_to_last + last_block_len)..(second_to_last + last_block_len),
+        last_block[last_block_len..].iter().copied(),
+    );
+    let last_plain_text = (0..last_block_len)
+        .map(|i| last_block[i] ^ plain_text[second_to_last + i])
+        .collect::<Vec<u8>>();
+
+    // The rest is plain CBC, without the last block
+    plain_text.truncate((blocks - 1) * C::BLOCK_SIZE);
+    cbc_decrypt_in_place(cipher, iv, &mut plain_text)?;
+    plain_text.extend_from_slice(&last_plain_text);
+
+    Some(plain_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::Aes128;
+
+    use super::*;
+    use crate::{block_cipher::tests::ToyCipher, modes::cbc_encrypt, set_1::hex_to_bytes};
+
+    const PLAIN_TEXT: &[u8] = b"I would like the General Gau's Chicken, please, and wonton soup.";
+
+    /// RFC 3962's AES-CTS examples, which are CS3 with a zero IV
+    #[test]
+    fn rfc_3962_vectors() {
+        let cipher = <Aes128 as BlockCipher>::new(b"chicken teriyaki");
+        let iv = [0; 16];
+
+        for (len, expected) in [
+            (17, "c6353568f2bf8cb4d8a580362da7ff7f97"),
+            (
+                31,
+                "fc00783e0efdb2c1d445d4c8eff7ed2297687268d6ecccc0c07b25e25ecfe5",
+            ),
+            (
+                32,
+                "39312523a78662d5be7fcbcc98ebf5a897687268d6ecccc0c07b25e25ecfe584",
+            ),
+            (
+                47,
+                "97687268d6ecccc0c07b25e25ecfe584b3fffd940c16a18c1b5549d2f838029e\
+                 39312523a78662d5be7fcbcc98ebf5",
+            ),
+            (
+                48,
+                "97687268d6ecccc0c07b25e25ecfe5849dad8bbb96c4cdc03bc103e1a194bbd8\
+                 39312523a78662d5be7fcbcc98ebf5a8",
+            ),
+            (
+                64,
+                "97687268d6ecccc0c07b25e25ecfe58439312523a78662d5be7fcbcc98ebf5a8\
+                 4807efe836ee89a526730dbc2f7bc8409dad8bbb96c4cdc03bc103e1a194bbd8",
+            ),
+        ] {
+            let expected = hex_to_bytes(expected).unwrap();
+            let plain_text = &PLAIN_TEXT[..len];
+
+            assert_eq!(
+                Some(expected.clone()),
+                cbc_cs_encrypt(&cipher, &iv, plain_text, CiphertextStealing::Cs3)
+            );
+            assert_eq!(
+                Some(plain_text.to_vec()),
+                cbc_cs_decrypt(&cipher, &iv, &expected, CiphertextStealing::Cs3)
+            );
+        }
+    }
+
+    #[test]
+    fn variants() {
+        let cipher = ToyCipher::new(b"toy key!");
+        let iv = b"toy iv!!";
+
+        for len in 8..=40 {
+            let plain_text = &PLAIN_TEXT[..len];
+            let [cs1, cs2, cs3] = [
+                CiphertextStealing::Cs1,
+                CiphertextStealing::Cs2,
+                CiphertextStealing::Cs3,
+            ]
+            .map(|variant| {
+                let cipher_text = cbc_cs_encrypt(&cipher, iv, plain_text, variant).unwrap();
+                assert_eq!(len, cipher_text.len());
+                assert_eq!(
+                    Some(plain_text.to_vec()),
+                    cbc_cs_decrypt(&cipher, iv, &cipher_text, variant)
+                );
+                cipher_text
+            });
+
+            if len % 8 == 0 {
+                // CS1 and CS2 are plain CBC on whole blocks
+                let cbc = cbc_encrypt(&cipher, iv, plain_text).unwrap();
+                assert_eq!(cbc, cs1);
+                assert_eq!(cbc, cs2);
+            } else {
+                assert_eq!(cs2, cs3);
+            }
+            if len > 8 {
+                assert_ne!(cs1, cs3);
+            }
+        }
+
+        assert_eq!(
+            None,
+            cbc_cs_encrypt(&cipher, iv, b"short", CiphertextStealing::Cs1)
+        );
+        assert_eq!(
+            None,
+            cbc_cs_decrypt(&cipher, iv, b"short", CiphertextStealing::Cs1)
+        );
+    }
+}
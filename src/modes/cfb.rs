@@ -0,0 +1,149 @@
+//! Cipher feedback mode, with 1, 8 and 128 bit segments
+
+use crate::block_cipher::BlockCipher;
+
+/// CFB with whole byte segments, the cipher text of each segment is shifted into the register for the next
+///
+/// Returning None indicates the IV is not a single block or the segment is longer than a block
+fn cfb_bytes<C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    text: &[u8],
+    segment_len: usize,
+    decrypt: bool,
+) -> Option<Vec<u8>> {
+    if iv.len() != C::BLOCK_SIZE || segment_len > C::BLOCK_SIZE {
+        return None;
+    }
+
+    let mut register = iv.to_vec();
+    let mut keystream = vec![0; C::BLOCK_SIZE];
+    let mut output = text.to_vec();
+    let feedback_start = C::BLOCK_SIZE - segment_len;
+
+    for segment in output.chunks_mut(segment_len) {
+        keystream.copy_from_slice(&register);
+        cipher.encrypt_block(&mut keystream);
+        register.copy_within(segment_len.., 0);
+
+        // Only the last segment can be short, and nothing is encrypted after it
+        let feedback = &mut register[feedback_start..(feedback_start + segment.len())];
+        if decrypt {
+            feedback.copy_from_slice(segment);
+        }
+        for (byte, keystream_byte) in segment.iter_mut().zip(&keystream) {
+            *byte ^= keystream_byte;
+        }
+        if !decrypt {
+            feedback.copy_from_slice(segment);
+        }
+    }
+
+    Some(output)
+}
+
+/// CFB with single bit segments, most significant bit of each byte first
+fn cfb_bits<C: BlockCipher>(cipher: &C, iv: &[u8], text: &[u8], decrypt: bool) -> Option<Vec<u8>> {
+    if iv.len() != C::BLOCK_SIZE {
+        return None;
+    }
+
+    let mut register = iv.to_vec();
+    let mut keystream = vec![0; C::BLOCK_SIZE];
+    let mut output = text.to_vec();
+
+    for i in 0..(output.len() * 8) {
+        keystream.copy_from_slice(&register);
+        cipher.encrypt_block(&mut keystream);
+
+        let shift = 7 - i % 8;
+        let input_bit = (output[i / 8] >> shift) & 1;
+        let output_bit = input_bit ^ (keystream[0] >> 7);
+        output[i / 8] ^= (input_bit ^ output_bit) << shift;
+
+        let cipher_text_bit = if decrypt { input_bit } else { output_bit };
+        for j in 0..(C::BLOCK_SIZE - 1) {
+            register[j] = (register[j] << 1) | (register[j + 1] >> 7);
+        }
+        register[C::BLOCK_SIZE - 1] = (register[C::BLOCK_SIZE - 1] << 1) | cipher_text_bit;
+    }
+
+    Some(output)
+}
+
+/// Encrypts with 1 bit segments, a block cipher call for every bit
+///
+/// Returning None indicates the IV is not a single block
+pub fn cfb1_encrypt<C: BlockCipher>(cipher: &C, iv: &[u8], plain_text: &[u8]) -> Option<Vec<u8>> {
+    cfb_bits(cipher, iv, plain_text, false)
+}
+
+/// Decrypts with 1 bit segments
+///
+/// Returning None indicates the IV is not a single block
+pub fn cfb1_decrypt<C: BlockCipher>(cipher: &C, iv: &[u8], cipher_text: &[u8]) -> Option<Vec<u8>> {
+    cfb_bits(cipher, iv, cipher_text, true)
+}
+
+/// Encrypts with 8 bit segments, a block cipher call for every byte
+///
+/// Returning None indicates the IV is not a single block
+pub fn cfb8_encrypt<C: BlockCipher>(cipher: &C, iv: &[u8], plain_text: &[u8]) -> Option<Vec<u8>> {
+    cfb_bytes(cipher, iv, plain_text, 1, false)
+}
+
+/// Decrypts with 8 bit segments
+///
+/// Returning None indicates the IV is not a single block
+pub fn cfb8_decrypt<C: BlockCipher>(cipher: &C, iv: &[u8], cipher_text: &[u8]) -> Option<Vec<u8>> {
+    cfb_bytes(cipher, iv, cipher_text, 1, true)
+}
+
+/// Encrypts with 128 bit segments, the last segment may be short
+///
+/// Returning None indicates the IV is not a single block or the cipher's blocks are shorter than 16 bytes
+pub fn cfb128_encrypt<C: BlockCipher>(cipher: &C, iv: &[u8], plain_text: &[u8]) -> Option<Vec<u8>> {
+    cfb_bytes(cipher, iv, plain_text, 16, false)
+}
+
+/// Decrypts with 128 bit segments, the last segment may be short
+///
+/// Returning None indicates the IV is not a single block or the cipher's blocks are shorter than 16 bytes
+pub fn cfb128_decrypt<C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    cipher_text: &[u8],
+) -> Option<Vec<u8>> {
+    cfb_bytes(cipher, iv, cipher_text, 16, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_cipher::tests::ToyCipher;
+
+    #[test]
+    fn round_trip() {
+        let cipher = ToyCipher::new(b"toy key!");
+        let iv = b"toy iv!!";
+        let plain_text = b"an odd length message".to_vec();
+
+        let cipher_text = cfb1_encrypt(&cipher, iv, &plain_text).unwrap();
+        assert_ne!(plain_text, cipher_text);
+        assert_eq!(
+            Some(plain_text.clone()),
+            cfb1_decrypt(&cipher, iv, &cipher_text)
+        );
+
+        let cipher_text = cfb8_encrypt(&cipher, iv, &plain_text).unwrap();
+        assert_ne!(plain_text, cipher_text);
+        assert_eq!(
+            Some(plain_text.clone()),
+            cfb8_decrypt(&cipher, iv, &cipher_text)
+        );
+
+        // The toy cipher's blocks are too short for 128 bit segments
+        assert_eq!(None, cfb128_encrypt(&cipher, iv, &plain_text));
+        assert_eq!(None, cfb8_encrypt(&cipher, &[0; 16], &plain_text));
+    }
+}
@@ -0,0 +1,144 @@
+//! Counter mode
+
+use crate::block_cipher::BlockCipher;
+
+/// Encrypts or decrypts the buffer in place, starting from block `first_block`
+///
+/// The counter block is the cryptopals format, a 64 bit little endian nonce followed by a 64 bit little endian block
+/// count. Starting part way through lets the buffer be split up and processed in pieces.
+///
+/// Returning None indicates the cipher's blocks are not 16 bytes
+pub fn ctr_apply_keystream<'a, C: BlockCipher>(
+    cipher: &C,
+    nonce: u64,
+    first_block: u64,
+    buffer: &'a mut [u8],
+) -> Option<&'a mut [u8]> {
+    if C::BLOCK_SIZE != 16 {
+        return None;
+    }
+
+    let mut keystream = [0; 16];
+    for (block_count, chunk) in (first_block..).zip(buffer.chunks_mut(16)) {
+        keystream[..8].copy_from_slice(&nonce.to_le_bytes());
+        keystream[8..].copy_from_slice(&block_count.to_le_bytes());
+        cipher.encrypt_block(&mut keystream);
+
+        for (byte, keystream_byte) in chunk.iter_mut().zip(keystream) {
+            *byte ^= keystream_byte;
+        }
+    }
+
+    Some(buffer)
+}
+
+/// Encrypts or decrypts text of any length
+///
+/// Returning None indicates the cipher's blocks are not 16 bytes
+pub fn ctr_apply<C: BlockCipher>(cipher: &C, nonce: u64, text: &[u8]) -> Option<Vec<u8>> {
+    let mut output = text.to_vec();
+    ctr_apply_keystream(cipher, nonce, 0, &mut output)?;
+    Some(output)
+}
+
+/// Encrypts or decrypts text of any length with SP 800-38A's counter blocks
+///
+/// The whole counter block is a big endian integer, incremented for each block and wrapping around at the top.
+///
+/// Returning None indicates the initial counter block is not a single block
+pub fn ctr_apply_with_counter<C: BlockCipher>(
+    cipher: &C,
+    initial_counter: &[u8],
+    text: &[u8],
+) -> Option<Vec<u8>> {
+    ctr_apply_with_counter_len(cipher, initial_counter, C::BLOCK_SIZE, text)
+}
+
+/// [ctr_apply_with_counter], incrementing only the last `counter_len` bytes of the counter block
+///
+/// The rest of the block is a fixed nonce, GCM's counter is the last 4 bytes.
+///
+/// Returning None indicates the initial counter block is not a single block or the counter is empty or longer than it
+pub fn ctr_apply_with_counter_len<C: BlockCipher>(
+    cipher: &C,
+    initial_counter: &[u8],
+    counter_len: usize,
+    text: &[u8],
+) -> Option<Vec<u8>> {
+    if initial_counter.len() != C::BLOCK_SIZE || counter_len == 0 || counter_len > C::BLOCK_SIZE {
+        return None;
+    }
+
+    let mut counter = initial_counter.to_vec();
+    let mut keystream = vec![0; C::BLOCK_SIZE];
+    let mut output = text.to_vec();
+    for chunk in output.chunks_mut(C::BLOCK_SIZE) {
+        keystream.copy_from_slice(&counter);
+        cipher.encrypt_block(&mut keystream);
+        for (byte, keystream_byte) in chunk.iter_mut().zip(&keystream) {
+            *byte ^= keystream_byte;
+        }
+
+        for byte in counter.iter_mut().rev().take(counter_len) {
+            *byte = byte.wrapping_add(1);
+            if *byte != 0 {
+                break;
+            }
+        }
+    }
+
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::Aes128;
+
+    use super::*;
+    use crate::block_cipher::tests::ToyCipher;
+
+    #[test]
+    fn split_keystream() {
+        let cipher = <Aes128 as BlockCipher>::new(b"YELLOW SUBMARINE");
+        let text = (0..100).collect::<Vec<u8>>();
+        let whole = ctr_apply(&cipher, 3, &text).unwrap();
+
+        let mut pieces = text.clone();
+        let (first, second) = pieces.split_at_mut(32);
+        ctr_apply_keystream(&cipher, 3, 0, first).unwrap();
+        ctr_apply_keystream(&cipher, 3, 2, second).unwrap();
+        assert_eq!(whole, pieces);
+
+        assert_eq!(None, ctr_apply(&ToyCipher::new(b"toy key!"), 3, &text));
+    }
+
+    #[test]
+    fn counter_wraps() {
+        let cipher = ToyCipher::new(b"toy key!");
+        let text = [0; 24];
+
+        let output = ctr_apply_with_counter(&cipher, &[0xff; 8], &text).unwrap();
+        let mut keystream = [[0xff; 8], [0; 8], [0, 0, 0, 0, 0, 0, 0, 1]];
+        for block in keystream.iter_mut() {
+            cipher.encrypt_block(block);
+        }
+        assert_eq!(keystream.concat(), output);
+        assert_eq!(None, ctr_apply_with_counter(&cipher, &[0; 16], &text));
+
+        // Only the counter part wraps
+        let output = ctr_apply_with_counter_len(&cipher, &[0xff; 8], 2, &text[..16]).unwrap();
+        let mut keystream = [[0xff; 8], [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0, 0]];
+        for block in keystream.iter_mut() {
+            cipher.encrypt_block(block);
+        }
+        assert_eq!(keystream.concat(), output);
+        assert_eq!(
+            None,
+            ctr_apply_with_counter_len(&cipher, &[0xff; 8], 0, &text)
+        );
+        assert_eq!(
+            None,
+            ctr_apply_with_counter_len(&cipher, &[0xff; 8], 9, &text)
+        );
+    }
+}
@@ -0,0 +1,163 @@
+//! Electronic codebook mode
+
+use crate::{block_cipher::BlockCipher, padding::Padding};
+
+/// Encrypts every block of the buffer in place
+///
+/// Returning None indicates the buffer is not a whole number of blocks
+pub fn ecb_encrypt_in_place<'a, C: BlockCipher>(
+    cipher: &C,
+    buffer: &'a mut [u8],
+) -> Option<&'a mut [u8]> {
+    if !buffer.len().is_multiple_of(C::BLOCK_SIZE) {
+        return None;
+    }
+
+    for block in buffer.chunks_exact_mut(C::BLOCK_SIZE) {
+        cipher.encrypt_block(block);
+    }
+
+    Some(buffer)
+}
+
+/// Decrypts every block of the buffer in place
+///
+/// Returning None indicates the buffer is not a whole number of blocks
+pub fn ecb_decrypt_in_place<'a, C: BlockCipher>(
+    cipher: &C,
+    buffer: &'a mut [u8],
+) -> Option<&'a mut [u8]> {
+    if !buffer.len().is_multiple_of(C::BLOCK_SIZE) {
+        return None;
+    }
+
+    for block in buffer.chunks_exact_mut(C::BLOCK_SIZE) {
+        cipher.decrypt_block(block);
+    }
+
+    Some(buffer)
+}
+
+/// Encrypts a whole number of blocks
+///
+/// Returning None indicates the plain text is not a whole number of blocks
+pub fn ecb_encrypt<C: BlockCipher>(cipher: &C, plain_text: &[u8]) -> Option<Vec<u8>> {
+    let mut cipher_text = plain_text.to_vec();
+    ecb_encrypt_in_place(cipher, &mut cipher_text)?;
+    Some(cipher_text)
+}
+
+/// Decrypts a whole number of blocks
+///
+/// Returning None indicates the cipher text is not a whole number of blocks
+pub fn ecb_decrypt<C: BlockCipher>(cipher: &C, cipher_text: &[u8]) -> Option<Vec<u8>> {
+    let mut plain_text = cipher_text.to_vec();
+    ecb_decrypt_in_place(cipher, &mut plain_text)?;
+    Some(plain_text)
+}
+
+/// Pads the plain text and encrypts it
+///
+/// Returning None indicates the padding can't fill out the last block
+pub fn ecb_encrypt_padded<C: BlockCipher, P: Padding>(
+    cipher: &C,
+    plain_text: &[u8],
+    padding: &P,
+) -> Option<Vec<u8>> {
+    let mut cipher_text = padding.pad(plain_text, C::BLOCK_SIZE)?;
+    ecb_encrypt_in_place(cipher, &mut cipher_text)?;
+    Some(cipher_text)
+}
+
+/// Decrypts the cipher text and removes its padding
+///
+/// Returning None indicates the cipher text is not a whole number of blocks or the padding is invalid
+pub fn ecb_decrypt_padded<C: BlockCipher, P: Padding>(
+    cipher: &C,
+    cipher_text: &[u8],
+    padding: &P,
+) -> Option<Vec<u8>> {
+    let mut plain_text = ecb_decrypt(cipher, cipher_text)?;
+    let unpadded_len = padding.unpadded_len(&plain_text, C::BLOCK_SIZE)?;
+    plain_text.truncate(unpadded_len);
+    Some(plain_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::{Aes128, Aes192, Aes256};
+
+    use super::*;
+    use crate::{block_cipher::tests::ToyCipher, fips_197, padding::AnsiX923, set_1::hex_to_bytes};
+
+    #[test]
+    fn sp_800_38a_vectors() {
+        let plain_text = hex_to_bytes("6bc1bee22e409f96e93d7e117393172a").unwrap();
+
+        let cipher = Aes128::new(
+            &hex_to_bytes("2b7e151628aed2a6abf7158809cf4f3c")
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        );
+        assert_eq!(
+            hex_to_bytes("3ad77bb40d7a3660a89ecaf32466ef97"),
+            ecb_encrypt(&cipher, &plain_text)
+        );
+
+        let cipher = Aes192::new(
+            &hex_to_bytes("8e73b0f7da0e6452c810f32b809079e562f8ead2522c6b7b")
+                .unwrap()
+                .try_into()
+                .unwrap(),
+        );
+        assert_eq!(
+            hex_to_bytes("bd334f1d6e45f25ff712a214571fa5cc"),
+            ecb_encrypt(&cipher, &plain_text)
+        );
+
+        let key = hex_to_bytes("603deb1015ca71be2b73aef0857d77811f352c073b6108d72d9810a30914dff4")
+            .unwrap();
+        let expected = hex_to_bytes("f3eed1bdb5d2a03c064b5a7e3db181f8");
+        assert_eq!(
+            expected,
+            ecb_encrypt(&Aes256::new(&key.clone().try_into().unwrap()), &plain_text)
+        );
+        assert_eq!(
+            expected,
+            ecb_encrypt(&<fips_197::Aes as BlockCipher>::new(&key), &plain_text)
+        );
+    }
+
+    #[test]
+    fn toy_cipher() {
+        let cipher = ToyCipher::new(b"toy key!");
+        let plain_text = b"sixteen byte msg";
+
+        let cipher_text = ecb_encrypt(&cipher, plain_text).unwrap();
+        assert_ne!(plain_text.to_vec(), cipher_text);
+        assert_eq!(
+            Some(plain_text.to_vec()),
+            ecb_decrypt(&cipher, &cipher_text)
+        );
+        assert_eq!(None, ecb_encrypt(&cipher, b"twelve bytes"));
+    }
+
+    #[test]
+    fn padded_toy_cipher() {
+        let cipher = ToyCipher::new(b"toy key!");
+
+        for plain_text in [b"".as_slice(), b"twelve bytes", b"sixteen byte msg"] {
+            let cipher_text = ecb_encrypt_padded(&cipher, plain_text, &AnsiX923).unwrap();
+            assert_eq!(plain_text.len() / 8 * 8 + 8, cipher_text.len());
+            assert_eq!(
+                Some(plain_text.to_vec()),
+                ecb_decrypt_padded(&cipher, &cipher_text, &AnsiX923)
+            );
+        }
+        assert_eq!(
+            None,
+            ecb_decrypt_padded(&cipher, b"twelve bytes", &AnsiX923)
+        );
+    }
+}
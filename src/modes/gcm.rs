@@ -0,0 +1,299 @@
+//! Galois/counter mode, from NIST SP 800-38D
+//!
+//! The plain text is encrypted with CTR mode and the tag is GHASH of the additional data and cipher text, a
+//! polynomial in the hash key H = E_K(0), masked with the encrypted first counter block.
+
+use super::{ctr::ctr_apply_with_counter_len, ecb::ecb_encrypt};
+use crate::{block_cipher::BlockCipher, gf128::Gf128};
+
+/// Tag lengths in bytes SP 800-38D allows, the 4 and 8 byte tags only for some applications
+const TAG_LENS: [usize; 7] = [4, 8, 12, 13, 14, 15, 16];
+
+/// GCM's counter is the last 32 bits of the counter block
+const COUNTER_LEN: usize = 4;
+
+/// Longest plain text in bytes, 2^32 - 2 blocks, before the 32 bit counter would wrap back round to J0
+const MAX_TEXT_LEN: u64 = ((1 << 32) - 2) * 16;
+
+/// The hash key H, the encrypted zero block
+///
+/// Returning None indicates the cipher's blocks are not 16 bytes
+pub fn gcm_hash_key<C: BlockCipher>(cipher: &C) -> Option<Gf128> {
+    if C::BLOCK_SIZE != 16 {
+        return None;
+    }
+    let h = ecb_encrypt(cipher, &[0; 16])?;
+    Some(Gf128::from_bytes(h.as_slice().try_into().unwrap()))
+}
+
+/// GHASH of the additional data and cipher text, each zero padded to whole blocks, then their lengths in bits
+///
+/// Every block is added to the running sum, which is then multiplied by H, making the result a polynomial in H with
+/// the blocks as its coefficients.
+pub fn ghash(h: Gf128, aad: &[u8], cipher_text: &[u8]) -> Gf128 {
+    let lengths = ghash_lengths_block(aad, cipher_text);
+    aad.chunks(16)
+        .chain(cipher_text.chunks(16))
+        .chain(std::iter::once(lengths.as_slice()))
+        .fold(Gf128::ZERO, |sum, block| {
+            (sum + Gf128::from_partial_block(block)) * h
+        })
+}
+
+/// The last block GHASH takes, the lengths of the additional data and cipher text in bits as 64 bit big endian numbers
+pub(crate) fn ghash_lengths_block(aad: &[u8], cipher_text: &[u8]) -> [u8; 16] {
+    let mut lengths = [0; 16];
+    lengths[..8].copy_from_slice(&(aad.len() as u64 * 8).to_be_bytes());
+    lengths[8..].copy_from_slice(&(cipher_text.len() as u64 * 8).to_be_bytes());
+    lengths
+}
+
+/// The first counter block J0, a 96 bit IV followed by a counter of 1, or GHASH of any other IV
+fn pre_counter_block(h: Gf128, iv: &[u8]) -> [u8; 16] {
+    if iv.len() == 12 {
+        let mut block = [0; 16];
+        block[..12].copy_from_slice(iv);
+        block[15] = 1;
+        block
+    } else {
+        ghash(h, &[], iv).to_bytes()
+    }
+}
+
+/// Increments the 32 bit counter at the end of the counter block
+fn inc_32(block: &[u8; 16]) -> [u8; 16] {
+    let mut block = *block;
+    let counter = u32::from_be_bytes(block[12..].try_into().unwrap()).wrapping_add(1);
+    block[12..].copy_from_slice(&counter.to_be_bytes());
+    block
+}
+
+/// The full tag, GHASH masked with the encrypted first counter block
+fn tag<C: BlockCipher>(
+    cipher: &C,
+    h: Gf128,
+    j0: &[u8; 16],
+    aad: &[u8],
+    cipher_text: &[u8],
+) -> [u8; 16] {
+    let mask = ecb_encrypt(cipher, j0).unwrap();
+    let mask = Gf128::from_bytes(mask.as_slice().try_into().unwrap());
+    (ghash(h, aad, cipher_text) + mask).to_bytes()
+}
+
+/// Encrypts and authenticates the plain text, and authenticates the additional data
+///
+/// The cipher text is followed by the tag, truncated to `tag_len` bytes.
+///
+/// Returning None indicates the cipher's blocks are not 16 bytes, the IV is empty, the tag length is not allowed or
+/// the plain text is longer than 2^32 - 2 blocks
+pub fn gcm_seal<C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    aad: &[u8],
+    plain_text: &[u8],
+    tag_len: usize,
+) -> Option<Vec<u8>> {
+    if iv.is_empty() || !TAG_LENS.contains(&tag_len) || plain_text.len() as u64 > MAX_TEXT_LEN {
+        return None;
+    }
+    let h = gcm_hash_key(cipher)?;
+    let j0 = pre_counter_block(h, iv);
+
+    let mut sealed = ctr_apply_with_counter_len(cipher, &inc_32(&j0), COUNTER_LEN, plain_text)?;
+    let tag = tag(cipher, h, &j0, aad, &sealed);
+    sealed.extend_from_slice(&tag[..tag_len]);
+    Some(sealed)
+}
+
+/// Checks the tag at the end of the sealed text and decrypts the cipher text before it
+///
+/// Returning None indicates the cipher's blocks are not 16 bytes, the IV is empty, the tag length is not allowed, the
+/// cipher text is longer than 2^32 - 2 blocks or the tag doesn't match
+pub fn gcm_open<C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    aad: &[u8],
+    sealed: &[u8],
+    tag_len: usize,
+) -> Option<Vec<u8>> {
+    if iv.is_empty()
+        || !TAG_LENS.contains(&tag_len)
+        || sealed.len() < tag_len
+        || (sealed.len() - tag_len) as u64 > MAX_TEXT_LEN
+    {
+        return None;
+    }
+    let h = gcm_hash_key(cipher)?;
+    let j0 = pre_counter_block(h, iv);
+
+    let (cipher_text, received_tag) = sealed.split_at(sealed.len() - tag_len);
+    let tag = tag(cipher, h, &j0, aad, cipher_text);
+    // Compare every byte, so the time taken doesn't give away how much of the tag matched
+    let difference = tag
+        .iter()
+        .zip(received_tag)
+        .fold(0, |difference, (a, b)| difference | (a ^ b));
+    if difference != 0 {
+        return None;
+    }
+
+    ctr_apply_with_counter_len(cipher, &inc_32(&j0), COUNTER_LEN, cipher_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::{Aes128, Aes256};
+
+    use super::*;
+    use crate::{block_cipher::tests::ToyCipher, set_1::hex_to_bytes};
+
+    const KEY: &str = "feffe9928665731c6d6a8f9467308308";
+    const IV: &str = "cafebabefacedbaddecaf888";
+    const PLAIN_TEXT: &str = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72\
+                              1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255";
+    const AAD: &str = "feedfacedeadbeeffeedfacedeadbeefabaddad2";
+
+    fn check<C: BlockCipher>(cipher: &C, iv: &str, aad: &str, plain_text: &str, expected: &str) {
+        let iv = hex_to_bytes(iv).unwrap();
+        let aad = hex_to_bytes(aad).unwrap();
+        let plain_text = hex_to_bytes(plain_text).unwrap();
+        let expected = hex_to_bytes(expected).unwrap();
+
+        assert_eq!(
+            Some(expected.clone()),
+            gcm_seal(cipher, &iv, &aad, &plain_text, 16)
+        );
+        assert_eq!(Some(plain_text), gcm_open(cipher, &iv, &aad, &expected, 16));
+    }
+
+    /// Test cases 1 to 6 from the GCM specification, AES-128
+    #[test]
+    fn aes_128_vectors() {
+        let cipher = <Aes128 as BlockCipher>::new(&[0; 16]);
+        check(
+            &cipher,
+            "000000000000000000000000",
+            "",
+            "",
+            "58e2fccefa7e3061367f1d57a4e7455a",
+        );
+        check(
+            &cipher,
+            "000000000000000000000000",
+            "",
+            "00000000000000000000000000000000",
+            "0388dace60b6a392f328c2b971b2fe78ab6e47d42cec13bdf53a67b21257bddf",
+        );
+
+        let cipher = <Aes128 as BlockCipher>::new(&hex_to_bytes(KEY).unwrap().try_into().unwrap());
+        assert_eq!(
+            Gf128::from_bytes(
+                &hex_to_bytes("b83b533708bf535d0aa6e52980d53b78")
+                    .unwrap()
+                    .try_into()
+                    .unwrap()
+            ),
+            gcm_hash_key(&cipher).unwrap()
+        );
+        check(
+            &cipher,
+            IV,
+            "",
+            PLAIN_TEXT,
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+             21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985\
+             4d5c2af327cd64a62cf35abd2ba6fab4",
+        );
+        let plain_text = &PLAIN_TEXT[..120];
+        check(
+            &cipher,
+            IV,
+            AAD,
+            plain_text,
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e\
+             21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091\
+             5bc94fbc3221a5db94fae95ae7121a47",
+        );
+        // A 64 bit IV, then a 480 bit one, are hashed into the first counter block
+        check(
+            &cipher,
+            "cafebabefacedbad",
+            AAD,
+            plain_text,
+            "61353b4c2806934a777ff51fa22a4755699b2a714fcdc6f83766e5f97b6c7423\
+             73806900e49f24b22b097544d4896b424989b5e1ebac0f07c23f4598\
+             3612d2e79e3b0785561be14aaca2fccb",
+        );
+        check(
+            &cipher,
+            "9313225df88406e555909c5aff5269aa6a7a9538534f7da1e4c303d2a318a728\
+             c3c0c95156809539fcf0e2429a6b525416aedbf5a0de6a57a637b39b",
+            AAD,
+            plain_text,
+            "8ce24998625615b603a033aca13fb894be9112a5c3a211a8ba262a3cca7e2ca7\
+             01e4a9a4fba43c90ccdcb281d48c7c6fd62875d2aca417034c34aee5\
+             619cc5aefffe0bfa462af43c1699d050",
+        );
+    }
+
+    /// Test cases 13 to 15 from the GCM specification, AES-256
+    #[test]
+    fn aes_256_vectors() {
+        let cipher = <Aes256 as BlockCipher>::new(&[0; 32]);
+        check(
+            &cipher,
+            "000000000000000000000000",
+            "",
+            "",
+            "530f8afbc74536b9a963b4f1c4cb738b",
+        );
+        check(
+            &cipher,
+            "000000000000000000000000",
+            "",
+            "00000000000000000000000000000000",
+            "cea7403d4d606b6e074ec5d3baf39d18d0d1c8a799996bf0265b98b5d48ab919",
+        );
+
+        let key = hex_to_bytes(&KEY.repeat(2)).unwrap().try_into().unwrap();
+        let cipher = <Aes256 as BlockCipher>::new(&key);
+        check(
+            &cipher,
+            IV,
+            "",
+            PLAIN_TEXT,
+            "522dc1f099567d07f47f37a32a84427d643a8cdcbfe5c0c97598a2bd2555d1aa\
+             8cb08e48590dbb3da7b08b1056828838c5f61e6393ba7a0abcc9f662898015ad\
+             b094dac5d93471bdec1a502270e3cc6c",
+        );
+    }
+
+    #[test]
+    fn tampering_and_truncation() {
+        let cipher = <Aes128 as BlockCipher>::new(&[7; 16]);
+        let iv = [1; 12];
+        let sealed = gcm_seal(&cipher, &iv, b"header", b"attack at dawn", 16).unwrap();
+
+        // A truncated tag is a prefix of the full tag
+        let truncated = gcm_seal(&cipher, &iv, b"header", b"attack at dawn", 12).unwrap();
+        assert_eq!(sealed[..26], truncated[..]);
+        assert_eq!(
+            Some(b"attack at dawn".to_vec()),
+            gcm_open(&cipher, &iv, b"header", &truncated, 12)
+        );
+
+        let mut tampered = sealed.clone();
+        tampered[0] ^= 1;
+        assert_eq!(None, gcm_open(&cipher, &iv, b"header", &tampered, 16));
+        assert_eq!(None, gcm_open(&cipher, &iv, b"footer", &sealed, 16));
+        assert_eq!(None, gcm_open(&cipher, &[2; 12], b"header", &sealed, 16));
+
+        assert_eq!(None, gcm_seal(&cipher, &iv, b"", b"", 11));
+        assert_eq!(None, gcm_seal(&cipher, &[], b"", b"", 16));
+        assert_eq!(
+            None,
+            gcm_seal(&ToyCipher::new(b"toy key!"), &iv, b"", b"", 16)
+        );
+    }
+}
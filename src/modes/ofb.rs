@@ -0,0 +1,44 @@
+//! Output feedback mode
+
+use crate::block_cipher::BlockCipher;
+
+/// Encrypts or decrypts text of any length, the keystream is the IV encrypted over and over
+///
+/// Returning None indicates the IV is not a single block
+pub fn ofb_apply<C: BlockCipher>(cipher: &C, iv: &[u8], text: &[u8]) -> Option<Vec<u8>> {
+    if iv.len() != C::BLOCK_SIZE {
+        return None;
+    }
+
+    let mut keystream = iv.to_vec();
+    let mut output = text.to_vec();
+    for chunk in output.chunks_mut(C::BLOCK_SIZE) {
+        cipher.encrypt_block(&mut keystream);
+        for (byte, keystream_byte) in chunk.iter_mut().zip(&keystream) {
+            *byte ^= keystream_byte;
+        }
+    }
+
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block_cipher::tests::ToyCipher;
+
+    #[test]
+    fn round_trip() {
+        let cipher = ToyCipher::new(b"toy key!");
+        let iv = b"toy iv!!";
+        let plain_text = b"an odd length message".to_vec();
+
+        let cipher_text = ofb_apply(&cipher, iv, &plain_text).unwrap();
+        assert_ne!(plain_text, cipher_text);
+        assert_eq!(
+            Some(plain_text.clone()),
+            ofb_apply(&cipher, iv, &cipher_text)
+        );
+        assert_eq!(None, ofb_apply(&cipher, &[0; 16], &plain_text));
+    }
+}
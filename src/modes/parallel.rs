@@ -0,0 +1,195 @@
+//! Multi-threaded ECB, CBC decryption and CTR for large buffers
+//!
+//! Every block of these only depends on the cipher text, or counter, so the buffer is split into chunks that are
+//! processed on rayon's thread pool. Buffers shorter than the threshold are processed on the calling thread, so the
+//! output is always the same as the serial functions'.
+
+use rayon::prelude::*;
+
+use super::{
+    cbc::cbc_decrypt_in_place,
+    ctr::ctr_apply_keystream,
+    ecb::{ecb_decrypt_in_place, ecb_encrypt_in_place},
+};
+use crate::block_cipher::BlockCipher;
+
+/// Buffers shorter than this many bytes aren't worth handing to other threads
+pub const DEFAULT_PARALLEL_THRESHOLD: usize = 64 * 1024;
+
+/// Number of blocks each thread processes at a time
+const CHUNK_BLOCKS: usize = 1024;
+
+/// [ecb_encrypt_in_place], using several threads when the buffer is at least `threshold` bytes
+///
+/// Returning None indicates the buffer is not a whole number of blocks
+pub fn ecb_encrypt_in_place_parallel<'a, C: BlockCipher + Sync>(
+    cipher: &C,
+    buffer: &'a mut [u8],
+    threshold: usize,
+) -> Option<&'a mut [u8]> {
+    if buffer.len() < threshold || !buffer.len().is_multiple_of(C::BLOCK_SIZE) {
+        return ecb_encrypt_in_place(cipher, buffer);
+    }
+
+    buffer
+        .par_chunks_mut(C::BLOCK_SIZE * CHUNK_BLOCKS)
+        .for_each(|chunk| {
+            ecb_encrypt_in_place(cipher, chunk);
+        });
+
+    Some(buffer)
+}
+
+/// [ecb_decrypt_in_place], using several threads when the buffer is at least `threshold` bytes
+///
+/// Returning None indicates the buffer is not a whole number of blocks
+pub fn ecb_decrypt_in_place_parallel<'a, C: BlockCipher + Sync>(
+    cipher: &C,
+    buffer: &'a mut [u8],
+    threshold: usize,
+) -> Option<&'a mut [u8]> {
+    if buffer.len() < threshold || !buffer.len().is_multiple_of(C::BLOCK_SIZE) {
+        return ecb_decrypt_in_place(cipher, buffer);
+    }
+
+    buffer
+        .par_chunks_mut(C::BLOCK_SIZE * CHUNK_BLOCKS)
+        .for_each(|chunk| {
+            ecb_decrypt_in_place(cipher, chunk);
+        });
+
+    Some(buffer)
+}
+
+/// [cbc_decrypt_in_place], using several threads when the buffer is at least `threshold` bytes
+///
+/// Each chunk is decrypted with the last cipher text block of the chunk before it as its IV. Those blocks are copied
+/// out first, as decrypting a chunk in place overwrites them.
+///
+/// Returning None indicates the IV is not a single block or the buffer is not a whole number of blocks
+pub fn cbc_decrypt_in_place_parallel<'a, C: BlockCipher + Sync>(
+    cipher: &C,
+    iv: &[u8],
+    buffer: &'a mut [u8],
+    threshold: usize,
+) -> Option<&'a mut [u8]> {
+    if buffer.len() < threshold
+        || iv.len() != C::BLOCK_SIZE
+        || !buffer.len().is_multiple_of(C::BLOCK_SIZE)
+    {
+        return cbc_decrypt_in_place(cipher, iv, buffer);
+    }
+
+    let chunk_len = C::BLOCK_SIZE * CHUNK_BLOCKS;
+    let ivs = std::iter::once(iv.to_vec())
+        .chain(
+            buffer
+                .chunks(chunk_len)
+                .map(|chunk| chunk[(chunk.len() - C::BLOCK_SIZE)..].to_vec()),
+        )
+        .collect::<Vec<Vec<u8>>>();
+
+    buffer
+        .par_chunks_mut(chunk_len)
+        .zip(ivs.par_iter())
+        .for_each(|(chunk, iv)| {
+            cbc_decrypt_in_place(cipher, iv, chunk);
+        });
+
+    Some(buffer)
+}
+
+/// [ctr_apply_keystream] from the first block, using several threads when the buffer is at least `threshold` bytes
+///
+/// Returning None indicates the cipher's blocks are not 16 bytes
+pub fn ctr_apply_keystream_parallel<'a, C: BlockCipher + Sync>(
+    cipher: &C,
+    nonce: u64,
+    buffer: &'a mut [u8],
+    threshold: usize,
+) -> Option<&'a mut [u8]> {
+    if buffer.len() < threshold || C::BLOCK_SIZE != 16 {
+        return ctr_apply_keystream(cipher, nonce, 0, buffer);
+    }
+
+    buffer
+        .par_chunks_mut(C::BLOCK_SIZE * CHUNK_BLOCKS)
+        .enumerate()
+        .for_each(|(i, chunk)| {
+            ctr_apply_keystream(cipher, nonce, (i * CHUNK_BLOCKS) as u64, chunk);
+        });
+
+    Some(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::Aes128;
+    use rand::RngCore;
+
+    use super::*;
+    use crate::{
+        block_cipher::tests::ToyCipher,
+        modes::{cbc_decrypt, ctr_apply, ecb_decrypt, ecb_encrypt},
+    };
+
+    /// Lengths either side of the chunk boundaries
+    const LENS: [usize; 4] = [
+        0,
+        16 * CHUNK_BLOCKS,
+        16 * CHUNK_BLOCKS + 16,
+        16 * 3 * CHUNK_BLOCKS - 48,
+    ];
+
+    #[test]
+    fn matches_serial() {
+        let cipher = <Aes128 as BlockCipher>::new(b"YELLOW SUBMARINE");
+        let iv = [7; 16];
+
+        for len in LENS {
+            let mut text = vec![0; len];
+            rand::thread_rng().fill_bytes(&mut text);
+
+            let mut buffer = text.clone();
+            ecb_encrypt_in_place_parallel(&cipher, &mut buffer, 0).unwrap();
+            assert_eq!(ecb_encrypt(&cipher, &text).unwrap(), buffer);
+
+            let mut buffer = text.clone();
+            ecb_decrypt_in_place_parallel(&cipher, &mut buffer, 0).unwrap();
+            assert_eq!(ecb_decrypt(&cipher, &text).unwrap(), buffer);
+
+            let mut buffer = text.clone();
+            cbc_decrypt_in_place_parallel(&cipher, &iv, &mut buffer, 0).unwrap();
+            assert_eq!(cbc_decrypt(&cipher, &iv, &text).unwrap(), buffer);
+
+            // CTR doesn't need whole blocks
+            let mut buffer = text[..len.saturating_sub(5)].to_vec();
+            ctr_apply_keystream_parallel(&cipher, 9, &mut buffer, 0).unwrap();
+            assert_eq!(
+                ctr_apply(&cipher, 9, &text[..len.saturating_sub(5)]).unwrap(),
+                buffer
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_input() {
+        let cipher = ToyCipher::new(b"toy key!");
+        let mut buffer = vec![0; 8 * CHUNK_BLOCKS + 1];
+
+        assert_eq!(None, ecb_encrypt_in_place_parallel(&cipher, &mut buffer, 0));
+        assert_eq!(None, ecb_decrypt_in_place_parallel(&cipher, &mut buffer, 0));
+        assert_eq!(
+            None,
+            cbc_decrypt_in_place_parallel(&cipher, &[0; 8], &mut buffer, 0)
+        );
+        assert_eq!(
+            None,
+            cbc_decrypt_in_place_parallel(&cipher, &[0; 16], &mut buffer[1..], 0)
+        );
+        assert_eq!(
+            None,
+            ctr_apply_keystream_parallel(&cipher, 0, &mut buffer, 0)
+        );
+    }
+}
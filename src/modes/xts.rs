@@ -0,0 +1,294 @@
+//! XTS, the IEEE 1619 disk encryption mode
+//!
+//! Each sector, or data unit, is encrypted on its own with a tweak made by encrypting the sector number with a
+//! second key. Block j of the sector is XORed with the tweak multiplied by x^j in GF(2^128) before and after
+//! encryption, so identical blocks encrypt differently across a sector and across sectors. A partial last block
+//! steals cipher text from the block before it.
+
+use aes::{Aes128, Aes256};
+
+use crate::block_cipher::BlockCipher;
+
+/// Multiplies the tweak by x in GF(2^128), with IEEE 1619's little endian bytes and x^128 + x^7 + x^2 + x + 1
+fn next_tweak(tweak: &mut [u8; 16]) {
+    let carry = tweak[15] >> 7;
+    for i in (1..16).rev() {
+        tweak[i] = (tweak[i] << 1) | (tweak[i - 1] >> 7);
+    }
+    tweak[0] = (tweak[0] << 1) ^ (carry * 0x87);
+}
+
+fn xor_tweak(block: &mut [u8], tweak: &[u8; 16]) {
+    for (byte, tweak_byte) in block.iter_mut().zip(tweak) {
+        *byte ^= tweak_byte;
+    }
+}
+
+/// The tweaks for every block of the sector, from the encrypted sector number
+fn tweaks<C: BlockCipher>(tweak_cipher: &C, sector: u128, blocks: usize) -> Vec<[u8; 16]> {
+    let mut tweak = sector.to_le_bytes();
+    tweak_cipher.encrypt_block(&mut tweak);
+
+    let mut tweaks = Vec::with_capacity(blocks);
+    for _ in 0..blocks {
+        tweaks.push(tweak);
+        next_tweak(&mut tweak);
+    }
+    tweaks
+}
+
+/// Encrypts or decrypts a sector, they only differ in the block function and which tweak steals from which
+fn xts<C: BlockCipher>(
+    data_cipher: &C,
+    tweak_cipher: &C,
+    sector: u128,
+    text: &[u8],
+    decrypt: bool,
+) -> Option<Vec<u8>> {
+    if C::BLOCK_SIZE != 16 || text.len() < 16 {
+        return None;
+    }
+
+    let blocks = text.len().div_ceil(16);
+    let last_block_len = text.len() - (blocks - 1) * 16;
+    let tweaks = tweaks(tweak_cipher, sector, blocks);
+    let process = |block: &mut [u8], tweak: &[u8; 16]| {
+        xor_tweak(block, tweak);
+        if decrypt {
+            data_cipher.decrypt_block(block);
+        } else {
+            data_cipher.encrypt_block(block);
+        }
+        xor_tweak(block, tweak);
+    };
+
+    let mut output = text.to_vec();
+    let whole_blocks = if last_block_len == 16 {
+        blocks
+    } else {
+        blocks - 2
+    };
+    for (block, tweak) in output.chunks_exact_mut(16).zip(&tweaks).take(whole_blocks) {
+        process(block, tweak);
+    }
+    if last_block_len == 16 {
+        return Some(output);
+    }
+
+    // The second to last block is processed with the last tweak when decrypting, as it was encrypted last
+    let (second_to_last_tweak, last_tweak) = if decrypt {
+        (&tweaks[blocks - 1], &tweaks[blocks - 2])
+    } else {
+        (&tweaks[blocks - 2], &tweaks[blocks - 1])
+    };
+    let (second_to_last, last) = output[((blocks - 2) * 16)..].split_at_mut(16);
+
+    process(second_to_last, second_to_last_tweak);
+    // The partial block takes the start of the processed block, and the rest of it fills out the partial block
+    second_to_last[..last_block_len].swap_with_slice(last);
+    process(second_to_last, last_tweak);
+
+    Some(output)
+}
+
+/// Encrypts a sector of at least one block into cipher text of the same length
+///
+/// Returning None indicates the cipher's blocks are not 16 bytes or the sector is shorter than a block
+pub fn xts_encrypt<C: BlockCipher>(
+    data_cipher: &C,
+    tweak_cipher: &C,
+    sector: u128,
+    plain_text: &[u8],
+) -> Option<Vec<u8>> {
+    xts(data_cipher, tweak_cipher, sector, plain_text, false)
+}
+
+/// Decrypts a sector of at least one block
+///
+/// Returning None indicates the cipher's blocks are not 16 bytes or the sector is shorter than a block
+pub fn xts_decrypt<C: BlockCipher>(
+    data_cipher: &C,
+    tweak_cipher: &C,
+    sector: u128,
+    cipher_text: &[u8],
+) -> Option<Vec<u8>> {
+    xts(data_cipher, tweak_cipher, sector, cipher_text, true)
+}
+
+/// Splits an XTS key into the data and tweak ciphers
+fn split_key<C: BlockCipher<Key = [u8; N]>, const N: usize>(key: &[u8]) -> (C, C) {
+    let (data_key, tweak_key) = key.split_at(N);
+    (
+        C::new(data_key.try_into().unwrap()),
+        C::new(tweak_key.try_into().unwrap()),
+    )
+}
+
+/// Encrypts a sector with XTS-AES-128, the 32 byte key is the data key followed by the tweak key
+///
+/// Returning None indicates the sector is shorter than a block
+pub fn xts_aes_128_encrypt(key: &[u8; 32], sector: u128, plain_text: &[u8]) -> Option<Vec<u8>> {
+    let (data_cipher, tweak_cipher) = split_key::<Aes128, 16>(key);
+    xts_encrypt(&data_cipher, &tweak_cipher, sector, plain_text)
+}
+
+/// Decrypts a sector with XTS-AES-128, the 32 byte key is the data key followed by the tweak key
+///
+/// Returning None indicates the sector is shorter than a block
+pub fn xts_aes_128_decrypt(key: &[u8; 32], sector: u128, cipher_text: &[u8]) -> Option<Vec<u8>> {
+    let (data_cipher, tweak_cipher) = split_key::<Aes128, 16>(key);
+    xts_decrypt(&data_cipher, &tweak_cipher, sector, cipher_text)
+}
+
+/// Encrypts a sector with XTS-AES-256, the 64 byte key is the data key followed by the tweak key
+///
+/// Returning None indicates the sector is shorter than a block
+pub fn xts_aes_256_encrypt(key: &[u8; 64], sector: u128, plain_text: &[u8]) -> Option<Vec<u8>> {
+    let (data_cipher, tweak_cipher) = split_key::<Aes256, 32>(key);
+    xts_encrypt(&data_cipher, &tweak_cipher, sector, plain_text)
+}
+
+/// Decrypts a sector with XTS-AES-256, the 64 byte key is the data key followed by the tweak key
+///
+/// Returning None indicates the sector is shorter than a block
+pub fn xts_aes_256_decrypt(key: &[u8; 64], sector: u128, cipher_text: &[u8]) -> Option<Vec<u8>> {
+    let (data_cipher, tweak_cipher) = split_key::<Aes256, 32>(key);
+    xts_decrypt(&data_cipher, &tweak_cipher, sector, cipher_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{block_cipher::tests::ToyCipher, set_1::hex_to_bytes};
+
+    /// Vectors from annex B of IEEE 1619, the 512 byte data units of vectors 4 and 10 are cut to their first two
+    /// blocks, which don't depend on the rest. The standard writes data unit sequence numbers as little endian bytes.
+    #[test]
+    fn ieee_1619_vectors() {
+        for (key1, key2, sector, plain_text, cipher_text) in [
+            // Vector 1
+            (
+                "00000000000000000000000000000000",
+                "00000000000000000000000000000000",
+                0,
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "917cf69ebd68b2ec9b9fe9a3eadda692cd43d2f59598ed858c02c2652fbf922e",
+            ),
+            // Vector 2
+            (
+                "11111111111111111111111111111111",
+                "22222222222222222222222222222222",
+                0x3333333333,
+                "4444444444444444444444444444444444444444444444444444444444444444",
+                "c454185e6a16936e39334038acef838bfb186fff7480adc4289382ecd6d394f0",
+            ),
+            // Vector 3
+            (
+                "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+                "22222222222222222222222222222222",
+                0x3333333333,
+                "4444444444444444444444444444444444444444444444444444444444444444",
+                "af85336b597afc1a900b2eb21ec949d292df4c047e0b21532186a5971a227a89",
+            ),
+            // Vector 4
+            (
+                "27182818284590452353602874713526",
+                "31415926535897932384626433832795",
+                0,
+                "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f",
+                "27a7479befa1d476489f308cd4cfa6e2a96e4bbe3208ff25287dd3819616e89c",
+            ),
+            // Vectors 15 to 18, with ciphertext stealing
+            (
+                "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+                "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0",
+                0x123456789a,
+                "000102030405060708090a0b0c0d0e0f10",
+                "6c1625db4671522d3d7599601de7ca09ed",
+            ),
+            (
+                "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+                "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0",
+                0x123456789a,
+                "000102030405060708090a0b0c0d0e0f1011",
+                "d069444b7a7e0cab09e24447d24deb1fedbf",
+            ),
+            (
+                "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+                "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0",
+                0x123456789a,
+                "000102030405060708090a0b0c0d0e0f101112",
+                "e5df1351c0544ba1350b3363cd8ef4beedbf9d",
+            ),
+            (
+                "fffefdfcfbfaf9f8f7f6f5f4f3f2f1f0",
+                "bfbebdbcbbbab9b8b7b6b5b4b3b2b1b0",
+                0x123456789a,
+                "000102030405060708090a0b0c0d0e0f10111213",
+                "9d84c813f719aa2c7be3f66171c7c5c2edbf9dac",
+            ),
+        ] {
+            let key = hex_to_bytes(&format!("{key1}{key2}"))
+                .unwrap()
+                .try_into()
+                .unwrap();
+            let plain_text = hex_to_bytes(plain_text).unwrap();
+            let cipher_text = hex_to_bytes(cipher_text).unwrap();
+
+            assert_eq!(
+                Some(cipher_text.clone()),
+                xts_aes_128_encrypt(&key, sector, &plain_text)
+            );
+            assert_eq!(
+                Some(plain_text),
+                xts_aes_128_decrypt(&key, sector, &cipher_text)
+            );
+        }
+
+        // Vector 10, XTS-AES-256
+        let key = hex_to_bytes(
+            "2718281828459045235360287471352662497757247093699959574966967627\
+             3141592653589793238462643383279502884197169399375105820974944592",
+        )
+        .unwrap()
+        .try_into()
+        .unwrap();
+        let plain_text = (0..32).collect::<Vec<u8>>();
+        let cipher_text =
+            hex_to_bytes("1c3b3a102f770386e4836c99e370cf9bea00803f5e482357a4ae12d414a3e63b")
+                .unwrap();
+        assert_eq!(
+            Some(cipher_text.clone()),
+            xts_aes_256_encrypt(&key, 0xff, &plain_text)
+        );
+        assert_eq!(
+            Some(plain_text),
+            xts_aes_256_decrypt(&key, 0xff, &cipher_text)
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let key = [7; 32];
+        let sector = (0..100).collect::<Vec<u8>>();
+
+        for len in 16..100 {
+            let cipher_text = xts_aes_128_encrypt(&key, 5, &sector[..len]).unwrap();
+            assert_eq!(len, cipher_text.len());
+            assert_eq!(
+                Some(sector[..len].to_vec()),
+                xts_aes_128_decrypt(&key, 5, &cipher_text)
+            );
+        }
+
+        // The same data encrypts differently in another sector, and in another block of the sector
+        let zeros = [0; 32];
+        let first = xts_aes_128_encrypt(&key, 0, &zeros).unwrap();
+        assert_ne!(first, xts_aes_128_encrypt(&key, 1, &zeros).unwrap());
+        assert_ne!(first[..16], first[16..]);
+
+        assert_eq!(None, xts_aes_128_encrypt(&key, 0, &zeros[..15]));
+        let toy = ToyCipher::new(b"toy key!");
+        assert_eq!(None, xts_encrypt(&toy, &toy, 0, &zeros));
+    }
+}
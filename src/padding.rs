@@ -0,0 +1,292 @@
+//! Padding schemes that fill out the last block for the block cipher modes
+
+use rand::RngCore;
+
+/// A way of padding text to a whole number of blocks, and of checking and removing it again
+pub trait Padding {
+    /// The bytes appended to `text_len` bytes of text to fill out its last block
+    ///
+    /// Returning None indicates the scheme can't pad text of that length to that block size
+    fn padding(&self, text_len: usize, block_size: usize) -> Option<Vec<u8>>;
+
+    /// Length of the padded text once its padding is removed
+    ///
+    /// Returning None indicates the text is not a whole number of blocks or its padding is invalid
+    fn unpadded_len(&self, padded: &[u8], block_size: usize) -> Option<usize>;
+
+    /// Copies the text with its padding appended
+    ///
+    /// Returning None indicates the scheme can't pad text of that length to that block size
+    fn pad(&self, text: &[u8], block_size: usize) -> Option<Vec<u8>> {
+        let mut padded = text.to_vec();
+        padded.extend_from_slice(&self.padding(text.len(), block_size)?);
+        Some(padded)
+    }
+
+    /// The padded text without its padding
+    ///
+    /// Returning None indicates the text is not a whole number of blocks or its padding is invalid
+    fn unpad<'a>(&self, padded: &'a [u8], block_size: usize) -> Option<&'a [u8]> {
+        Some(&padded[..self.unpadded_len(padded, block_size)?])
+    }
+}
+
+/// The length of padding that ends in a length byte, always at least one byte and at most a block
+fn length_byte_padding_len(text_len: usize, block_size: usize) -> Option<u8> {
+    if block_size == 0 || block_size > 255 {
+        return None;
+    }
+    Some((block_size - text_len % block_size) as u8)
+}
+
+/// The padding length from the last byte of padding that ends in a length byte, with the padding before it
+fn length_byte_padding(padded: &[u8], block_size: usize) -> Option<(usize, &[u8])> {
+    if block_size == 0 || !padded.len().is_multiple_of(block_size) {
+        return None;
+    }
+
+    let padding_len = *padded.last()? as usize;
+    if padding_len == 0 || padding_len > block_size {
+        return None;
+    }
+
+    let padding = &padded[(padded.len() - padding_len)..(padded.len() - 1)];
+    Some((padded.len() - padding_len, padding))
+}
+
+/// PKCS#7, every padding byte is the number of padding bytes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Pkcs7;
+
+impl Padding for Pkcs7 {
+    fn padding(&self, text_len: usize, block_size: usize) -> Option<Vec<u8>> {
+        let padding_len = length_byte_padding_len(text_len, block_size)?;
+        Some(vec![padding_len; padding_len as usize])
+    }
+
+    fn unpadded_len(&self, padded: &[u8], block_size: usize) -> Option<usize> {
+        let (unpadded_len, padding) = length_byte_padding(padded, block_size)?;
+        let padding_len = padded.len() - unpadded_len;
+        padding
+            .iter()
+            .all(|byte| *byte as usize == padding_len)
+            .then_some(unpadded_len)
+    }
+}
+
+/// ANSI X9.23, zeros followed by the number of padding bytes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AnsiX923;
+
+impl Padding for AnsiX923 {
+    fn padding(&self, text_len: usize, block_size: usize) -> Option<Vec<u8>> {
+        let padding_len = length_byte_padding_len(text_len, block_size)?;
+        let mut padding = vec![0; padding_len as usize];
+        *padding.last_mut()? = padding_len;
+        Some(padding)
+    }
+
+    fn unpadded_len(&self, padded: &[u8], block_size: usize) -> Option<usize> {
+        let (unpadded_len, padding) = length_byte_padding(padded, block_size)?;
+        padding
+            .iter()
+            .all(|byte| *byte == 0)
+            .then_some(unpadded_len)
+    }
+}
+
+/// ISO 10126, random bytes followed by the number of padding bytes
+///
+/// Only the length byte can be checked when unpadding
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Iso10126;
+
+impl Padding for Iso10126 {
+    fn padding(&self, text_len: usize, block_size: usize) -> Option<Vec<u8>> {
+        let padding_len = length_byte_padding_len(text_len, block_size)?;
+        let mut padding = vec![0; padding_len as usize];
+        rand::thread_rng().fill_bytes(&mut padding);
+        *padding.last_mut()? = padding_len;
+        Some(padding)
+    }
+
+    fn unpadded_len(&self, padded: &[u8], block_size: usize) -> Option<usize> {
+        length_byte_padding(padded, block_size).map(|(unpadded_len, _)| unpadded_len)
+    }
+}
+
+/// ISO/IEC 7816-4, a single 0x80 byte followed by zeros
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Iso7816_4;
+
+impl Padding for Iso7816_4 {
+    fn padding(&self, text_len: usize, block_size: usize) -> Option<Vec<u8>> {
+        if block_size == 0 {
+            return None;
+        }
+
+        let mut padding = vec![0; block_size - text_len % block_size];
+        padding[0] = 0x80;
+        Some(padding)
+    }
+
+    fn unpadded_len(&self, padded: &[u8], block_size: usize) -> Option<usize> {
+        if block_size == 0 || !padded.len().is_multiple_of(block_size) {
+            return None;
+        }
+
+        let zeros = padded.iter().rev().take_while(|byte| **byte == 0).count();
+        if zeros >= block_size || padded.len() == zeros || padded[padded.len() - zeros - 1] != 0x80
+        {
+            return None;
+        }
+        Some(padded.len() - zeros - 1)
+    }
+}
+
+/// Zeros up to the end of the block, nothing if the text already fills it
+///
+/// Unpadding removes the trailing zeros of the last block, so text that ends in zeros loses them
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZeroPadding;
+
+impl Padding for ZeroPadding {
+    fn padding(&self, text_len: usize, block_size: usize) -> Option<Vec<u8>> {
+        if block_size == 0 {
+            return None;
+        }
+        Some(vec![0; (block_size - text_len % block_size) % block_size])
+    }
+
+    fn unpadded_len(&self, padded: &[u8], block_size: usize) -> Option<usize> {
+        if block_size == 0 || !padded.len().is_multiple_of(block_size) {
+            return None;
+        }
+
+        let zeros = padded
+            .iter()
+            .rev()
+            .take(block_size - 1)
+            .take_while(|byte| **byte == 0)
+            .count();
+        Some(padded.len() - zeros)
+    }
+}
+
+/// No padding, the text must already be a whole number of blocks
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoPadding;
+
+impl Padding for NoPadding {
+    fn padding(&self, text_len: usize, block_size: usize) -> Option<Vec<u8>> {
+        (block_size != 0 && text_len.is_multiple_of(block_size)).then(Vec::new)
+    }
+
+    fn unpadded_len(&self, padded: &[u8], block_size: usize) -> Option<usize> {
+        (block_size != 0 && padded.len().is_multiple_of(block_size)).then_some(padded.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<P: Padding>(padding: &P, always_pads: bool) {
+        for block_size in [1, 8, 16] {
+            for len in 0..40 {
+                let text = vec![0xaa; len];
+                let padded = padding.pad(&text, block_size).unwrap();
+
+                assert!(padded.len().is_multiple_of(block_size));
+                assert!(padded.len() - len <= block_size);
+                assert_eq!(always_pads, padded.len() > len);
+                assert_eq!(Some(text.as_slice()), padding.unpad(&padded, block_size));
+                if block_size > 1 {
+                    assert_eq!(None, padding.unpad(&padded[1..], block_size));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn pkcs_7() {
+        round_trip(&Pkcs7, true);
+        assert_eq!(
+            Some(b"YELLOW SUBMARINE\x04\x04\x04\x04".to_vec()),
+            Pkcs7.pad(b"YELLOW SUBMARINE", 20)
+        );
+
+        assert_eq!(
+            Some(b"ICE ICE BABY".as_slice()),
+            Pkcs7.unpad(b"ICE ICE BABY\x04\x04\x04\x04", 16)
+        );
+        assert_eq!(None, Pkcs7.unpad(b"ICE ICE BABY\x05\x05\x05\x05", 16));
+        assert_eq!(None, Pkcs7.unpad(b"ICE ICE BABY\x01\x02\x03\x04", 16));
+        assert_eq!(None, Pkcs7.unpad(b"ICE ICE BABY\x00\x00\x00\x00", 16));
+
+        // The length doesn't fit in a byte
+        assert_eq!(None, Pkcs7.pad(b"", 256));
+    }
+
+    #[test]
+    fn ansi_x9_23() {
+        round_trip(&AnsiX923, true);
+        assert_eq!(
+            Some(vec![1, 2, 3, 0, 0, 0, 0, 5]),
+            AnsiX923.pad(&[1, 2, 3], 8)
+        );
+        assert_eq!(None, AnsiX923.unpad(&[1, 2, 3, 0, 0, 1, 0, 5], 8));
+        assert_eq!(None, AnsiX923.pad(b"", 256));
+    }
+
+    #[test]
+    fn iso_10126() {
+        round_trip(&Iso10126, true);
+        assert_eq!(5, Iso10126.pad(&[1, 2, 3], 8).unwrap()[7]);
+        assert_eq!(
+            Some([1, 2, 3].as_slice()),
+            Iso10126.unpad(&[1, 2, 3, 9, 9, 9, 9, 5], 8)
+        );
+        assert_eq!(None, Iso10126.unpad(&[1, 2, 3, 9, 9, 9, 9, 9], 8));
+    }
+
+    #[test]
+    fn iso_7816_4() {
+        round_trip(&Iso7816_4, true);
+        assert_eq!(
+            Some(vec![1, 2, 3, 0x80, 0, 0, 0, 0]),
+            Iso7816_4.pad(&[1, 2, 3], 8)
+        );
+        assert_eq!(None, Iso7816_4.unpad(&[1, 2, 3, 0x81, 0, 0, 0, 0], 8));
+        assert_eq!(None, Iso7816_4.unpad(&[0; 8], 8));
+        // Works for any block size
+        assert_eq!(300, Iso7816_4.pad(&[1], 300).unwrap().len());
+    }
+
+    #[test]
+    fn zero_padding() {
+        for block_size in [1, 8, 16] {
+            for len in 0..40 {
+                let text = vec![0xaa; len];
+                let padded = ZeroPadding.pad(&text, block_size).unwrap();
+                assert_eq!(len.div_ceil(block_size) * block_size, padded.len());
+                assert_eq!(
+                    Some(text.as_slice()),
+                    ZeroPadding.unpad(&padded, block_size)
+                );
+            }
+        }
+
+        // Trailing zeros in the text are taken for padding
+        assert_eq!(Some([1].as_slice()), ZeroPadding.unpad(&[1, 0, 0, 0], 4));
+        assert_eq!(None, ZeroPadding.unpad(&[1, 0, 0], 4));
+    }
+
+    #[test]
+    fn no_padding() {
+        assert_eq!(Some(vec![1; 16]), NoPadding.pad(&[1; 16], 8));
+        assert_eq!(None, NoPadding.pad(&[1; 15], 8));
+        assert_eq!(Some([1; 16].as_slice()), NoPadding.unpad(&[1; 16], 8));
+        assert_eq!(None, NoPadding.unpad(&[1; 15], 8));
+    }
+}
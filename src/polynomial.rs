@@ -0,0 +1,563 @@
+//! Polynomials over finite fields, and factoring them with Cantor–Zassenhaus
+
+use std::{
+    fmt::Debug,
+    ops::{Add, Mul, Sub},
+};
+
+use num_bigint::BigUint;
+use num_traits::ToPrimitive;
+use rand::Rng;
+
+/// A finite field, enough of one to factor polynomials over it
+pub trait Field:
+    Clone + PartialEq + Eq + Debug + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    fn zero() -> Self;
+
+    fn one() -> Self;
+
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+
+    /// Returning None indicates zero
+    fn inverse(&self) -> Option<Self>;
+
+    /// The number of elements q
+    fn order() -> BigUint;
+
+    /// The prime p that one adds up to zero after
+    fn characteristic() -> BigUint;
+
+    /// One added to itself n times
+    fn from_integer(n: usize) -> Self;
+
+    /// The root undoing a^p, which always exists in a finite field
+    fn pth_root(&self) -> Self;
+
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> Self;
+}
+
+/// A polynomial with coefficients in a finite field, the constant term first
+///
+/// Zero leading coefficients are trimmed, so the zero polynomial has no coefficients.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Polynomial<F>(Vec<F>);
+
+impl<F: Field> Default for Polynomial<F> {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+impl<F: Field> Polynomial<F> {
+    pub fn new(mut coefficients: Vec<F>) -> Self {
+        while coefficients.last().is_some_and(|c| c.is_zero()) {
+            coefficients.pop();
+        }
+        Polynomial(coefficients)
+    }
+
+    pub fn zero() -> Self {
+        Polynomial(Vec::new())
+    }
+
+    pub fn constant(c: F) -> Self {
+        Self::new(vec![c])
+    }
+
+    pub fn one() -> Self {
+        Self::constant(F::one())
+    }
+
+    /// The polynomial x
+    pub fn x() -> Self {
+        Polynomial(vec![F::zero(), F::one()])
+    }
+
+    pub fn coefficients(&self) -> &[F] {
+        &self.0
+    }
+
+    /// Returning None indicates the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.0.len().checked_sub(1)
+    }
+
+    /// Returning None indicates the zero polynomial
+    pub fn leading_coefficient(&self) -> Option<&F> {
+        self.0.last()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn is_one(&self) -> bool {
+        self.0.len() == 1 && self.0[0] == F::one()
+    }
+
+    /// The value of the polynomial at x, by Horner's rule
+    pub fn evaluate(&self, x: &F) -> F {
+        self.0
+            .iter()
+            .rev()
+            .fold(F::zero(), |sum, c| sum * x.clone() + c.clone())
+    }
+
+    fn scale(&self, factor: &F) -> Self {
+        Self::new(self.0.iter().map(|c| c.clone() * factor.clone()).collect())
+    }
+
+    /// The polynomial divided by its leading coefficient
+    ///
+    /// Returning None indicates the zero polynomial
+    pub fn monic(&self) -> Option<Self> {
+        Some(self.scale(&self.leading_coefficient()?.inverse()?))
+    }
+
+    /// The quotient and remainder of long division
+    ///
+    /// Returning None indicates the divisor is zero
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        let divisor_degree = divisor.degree()?;
+        let inverse = divisor.leading_coefficient()?.inverse()?;
+        if self.0.len() <= divisor_degree {
+            return Some((Self::zero(), self.clone()));
+        }
+
+        let mut remainder = self.0.clone();
+        let mut quotient = vec![F::zero(); remainder.len() - divisor_degree];
+        for i in (0..quotient.len()).rev() {
+            let c = remainder[i + divisor_degree].clone() * inverse.clone();
+            for (r, d) in remainder[i..].iter_mut().zip(&divisor.0) {
+                *r = r.clone() - c.clone() * d.clone();
+            }
+            quotient[i] = c;
+        }
+        remainder.truncate(divisor_degree);
+        Some((Self::new(quotient), Self::new(remainder)))
+    }
+
+    /// Division by a divisor known to be non-zero
+    fn div(&self, divisor: &Self) -> Self {
+        self.div_rem(divisor).unwrap().0
+    }
+
+    /// Remainder by a modulus known to be non-zero
+    fn rem(&self, modulus: &Self) -> Self {
+        self.div_rem(modulus).unwrap().1
+    }
+
+    /// The monic greatest common divisor, zero only if both polynomials are zero
+    pub fn gcd(&self, other: &Self) -> Self {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            let r = a.rem(&b);
+            a = b;
+            b = r;
+        }
+        a.monic().unwrap_or_default()
+    }
+
+    /// The polynomial raised to the exponent, modulo the modulus, by square and multiply
+    ///
+    /// Returning None indicates the modulus is zero
+    pub fn pow_mod(&self, exponent: &BigUint, modulus: &Self) -> Option<Self> {
+        let base = self.div_rem(modulus)?.1;
+        let mut result = Self::one().rem(modulus);
+        for i in (0..exponent.bits()).rev() {
+            result = (&result * &result).rem(modulus);
+            if exponent.bit(i) {
+                result = (&result * &base).rem(modulus);
+            }
+        }
+        Some(result)
+    }
+
+    /// The formal derivative
+    pub fn derivative(&self) -> Self {
+        Self::new(
+            self.0
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, c)| F::from_integer(i) * c.clone())
+                .collect(),
+        )
+    }
+
+    /// The p-th root of a polynomial in x^p, taking the p-th root of every coefficient
+    fn pth_root(&self) -> Self {
+        let p = F::characteristic().to_usize().unwrap();
+        Self::new(self.0.iter().step_by(p).map(F::pth_root).collect())
+    }
+
+    /// Square-free factors of the polynomial made monic, each with the multiplicity its irreducible factors have
+    ///
+    /// Returning None indicates the zero polynomial
+    pub fn square_free_factorization(&self) -> Option<Vec<(Self, usize)>> {
+        let f = self.monic()?;
+        if f.degree() == Some(0) {
+            return Some(Vec::new());
+        }
+
+        let derivative = f.derivative();
+        if derivative.is_zero() {
+            // Only powers of x that are multiples of p, f is the p-th power of its p-th root. That needs a degree of
+            // at least p, so p fits in a usize here
+            return Some(times_characteristic(
+                f.pth_root().square_free_factorization()?,
+            ));
+        }
+
+        let mut factors = Vec::new();
+        let mut c = f.gcd(&derivative);
+        let mut w = f.div(&c);
+        let mut multiplicity = 1;
+        while !w.is_one() {
+            let y = w.gcd(&c);
+            let factor = w.div(&y);
+            if !factor.is_one() {
+                factors.push((factor, multiplicity));
+            }
+            c = c.div(&y);
+            w = y;
+            multiplicity += 1;
+        }
+        // What's left has multiplicities that are multiples of p
+        if !c.is_one() {
+            factors.extend(times_characteristic(
+                c.pth_root().square_free_factorization()?,
+            ));
+        }
+        Some(factors)
+    }
+
+    /// Splits a monic square-free polynomial into the products of its irreducible factors of each degree
+    ///
+    /// An irreducible polynomial of degree d divides x^(q^i) - x exactly when d divides i.
+    pub fn distinct_degree_factorization(&self) -> Vec<(Self, usize)> {
+        let q = F::order();
+        let mut factors = Vec::new();
+        let mut f = self.clone();
+        let mut h = Self::x();
+        let mut degree = 1;
+        while f.degree().is_some_and(|d| d >= 2 * degree) {
+            h = h.pow_mod(&q, &f).unwrap();
+            let g = f.gcd(&(&h - &Self::x()));
+            if !g.is_one() {
+                f = f.div(&g);
+                h = h.rem(&f);
+                factors.push((g, degree));
+            }
+            degree += 1;
+        }
+        if let Some(d @ 1..) = f.degree() {
+            factors.push((f, d));
+        }
+        factors
+    }
+
+    /// Splits a monic square-free polynomial whose irreducible factors all have the given degree into those factors
+    ///
+    /// For a random h, h^((q^d - 1) / 2) is 1 or -1 modulo each factor, so subtracting one leaves a polynomial that
+    /// shares about half of them. In characteristic 2 that's always 1, so the trace h + h^2 + h^4 + ... + h^(q^d / 2)
+    /// is used instead, which is 0 or 1 modulo each factor.
+    pub fn equal_degree_factorization(&self, degree: usize) -> Vec<Self> {
+        let Some(n) = self.degree() else {
+            return Vec::new();
+        };
+        if degree == 0 || n <= degree {
+            return vec![self.clone()];
+        }
+
+        let q_d = F::order().pow(degree as u32);
+        let mut rng = rand::thread_rng();
+        let mut factors = vec![self.clone()];
+        while factors.len() < n / degree {
+            let h = Self::new((0..n).map(|_| F::random(&mut rng)).collect());
+            let g = if q_d.bit(0) {
+                &h.pow_mod(&((&q_d - 1u32) / 2u32), self).unwrap() - &Self::one()
+            } else {
+                h.trace(q_d.bits() - 1, self)
+            };
+            factors = factors
+                .into_iter()
+                .flat_map(|u| {
+                    if u.degree() > Some(degree) {
+                        let common = u.gcd(&g);
+                        if !common.is_one() && common != u {
+                            return vec![u.div(&common), common];
+                        }
+                    }
+                    vec![u]
+                })
+                .collect();
+        }
+        factors
+    }
+
+    /// h + h^2 + h^4 + ... + h^(2^(bits - 1)) modulo the modulus
+    fn trace(&self, bits: u64, modulus: &Self) -> Self {
+        let mut power = self.rem(modulus);
+        let mut trace = power.clone();
+        for _ in 1..bits {
+            power = (&power * &power).rem(modulus);
+            trace = &trace + &power;
+        }
+        trace
+    }
+
+    /// The monic irreducible factors and their multiplicities
+    ///
+    /// Returning None indicates the zero polynomial
+    pub fn factor(&self) -> Option<Vec<(Self, usize)>> {
+        let mut factors = Vec::new();
+        for (square_free, multiplicity) in self.square_free_factorization()? {
+            for (product, degree) in square_free.distinct_degree_factorization() {
+                for factor in product.equal_degree_factorization(degree) {
+                    factors.push((factor, multiplicity));
+                }
+            }
+        }
+        Some(factors)
+    }
+
+    /// The distinct roots, only splitting off the linear factors
+    ///
+    /// Every element of the field is a root of x^q - x, so its gcd with the polynomial is the product of the
+    /// distinct linear factors, which is all that needs splitting.
+    ///
+    /// Returning None indicates the zero polynomial
+    pub fn roots(&self) -> Option<Vec<F>> {
+        let f = self.monic()?;
+        let x_q = Self::x().pow_mod(&F::order(), &f)?;
+        let linear = f.gcd(&(&x_q - &Self::x()));
+        Some(
+            linear
+                .equal_degree_factorization(1)
+                .into_iter()
+                .filter(|factor| factor.degree() == Some(1))
+                // x + a has the root -a
+                .map(|factor| F::zero() - factor.0[0].clone())
+                .collect(),
+        )
+    }
+}
+
+fn times_characteristic<F: Field>(
+    factors: Vec<(Polynomial<F>, usize)>,
+) -> Vec<(Polynomial<F>, usize)> {
+    let p = F::characteristic().to_usize().unwrap();
+    factors
+        .into_iter()
+        .map(|(factor, multiplicity)| (factor, p * multiplicity))
+        .collect()
+}
+
+impl<F: Field> Add for &Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn add(self, rhs: &Polynomial<F>) -> Polynomial<F> {
+        let len = self.0.len().max(rhs.0.len());
+        Polynomial::new(
+            (0..len)
+                .map(|i| match (self.0.get(i), rhs.0.get(i)) {
+                    (Some(a), Some(b)) => a.clone() + b.clone(),
+                    (Some(c), None) | (None, Some(c)) => c.clone(),
+                    (None, None) => unreachable!(),
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<F: Field> Sub for &Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn sub(self, rhs: &Polynomial<F>) -> Polynomial<F> {
+        let len = self.0.len().max(rhs.0.len());
+        Polynomial::new(
+            (0..len)
+                .map(|i| {
+                    let a = self.0.get(i).cloned().unwrap_or_else(F::zero);
+                    let b = rhs.0.get(i).cloned().unwrap_or_else(F::zero);
+                    a - b
+                })
+                .collect(),
+        )
+    }
+}
+
+impl<F: Field> Mul for &Polynomial<F> {
+    type Output = Polynomial<F>;
+
+    fn mul(self, rhs: &Polynomial<F>) -> Polynomial<F> {
+        if self.is_zero() || rhs.is_zero() {
+            return Polynomial::zero();
+        }
+        let mut product = vec![F::zero(); self.0.len() + rhs.0.len() - 1];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in rhs.0.iter().enumerate() {
+                product[i + j] = product[i + j].clone() + a.clone() * b.clone();
+            }
+        }
+        Polynomial::new(product)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gf128::Gf128;
+
+    fn random_element() -> Gf128 {
+        Gf128(rand::random())
+    }
+
+    fn linear(root: Gf128) -> Polynomial<Gf128> {
+        Polynomial::new(vec![root, Gf128::ONE])
+    }
+
+    #[test]
+    fn division() {
+        let a = Polynomial::new((0..7).map(|_| random_element()).collect());
+        let b = Polynomial::new((0..3).map(|_| random_element()).collect());
+        let (quotient, remainder) = a.div_rem(&b).unwrap();
+        assert_eq!(a, &(&quotient * &b) + &remainder);
+        assert!(remainder.degree() < b.degree());
+        assert_eq!(None, a.div_rem(&Polynomial::zero()));
+
+        let c = linear(random_element());
+        assert_eq!(c, (&a * &c).gcd(&(&b * &c)).gcd(&c));
+        assert_eq!(b.monic().unwrap(), b.gcd(&Polynomial::zero()));
+    }
+
+    #[test]
+    fn factoring() {
+        let [a, b, c] = [random_element(), random_element(), random_element()];
+        // A random quadratic is irreducible about half the time
+        let quadratic = Polynomial::new(vec![random_element(), Gf128::ONE, Gf128::ONE]);
+        let f = [
+            (linear(a), 3),
+            (linear(b), 1),
+            (linear(c), 2),
+            (quadratic.clone(), 1),
+        ]
+        .iter()
+        .fold(Polynomial::one(), |product, (factor, multiplicity)| {
+            (0..*multiplicity).fold(product, |product, _| &product * factor)
+        })
+        .scale(&random_element());
+
+        let factors = f.factor().unwrap();
+        let product = factors.iter().fold(
+            Polynomial::constant(*f.leading_coefficient().unwrap()),
+            |product, (factor, multiplicity)| {
+                (0..*multiplicity).fold(product, |product, _| &product * factor)
+            },
+        );
+        assert_eq!(f, product);
+        for (root, multiplicity) in [(a, 3), (b, 1), (c, 2)] {
+            assert!(factors.contains(&(linear(root), multiplicity)));
+        }
+
+        let roots = f.roots().unwrap();
+        for root in [a, b, c] {
+            assert!(roots.contains(&root));
+        }
+        for root in &roots {
+            assert_eq!(Gf128::ZERO, f.evaluate(root));
+        }
+        assert_eq!(3 + quadratic.roots().unwrap().len(), roots.len());
+        assert_eq!(None, Polynomial::<Gf128>::zero().roots());
+        assert_eq!(Some(vec![]), Polynomial::constant(a).roots());
+        assert_eq!(Some(vec![a]), (&linear(a) * &linear(a)).roots());
+    }
+
+    /// GF(2), where q^d - 1 is never a multiple of 3 for odd d
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Bit(bool);
+
+    impl Add for Bit {
+        type Output = Self;
+
+        #[allow(clippy::suspicious_arithmetic_impl)]
+        fn add(self, rhs: Self) -> Self {
+            Bit(self.0 ^ rhs.0)
+        }
+    }
+
+    impl Sub for Bit {
+        type Output = Self;
+
+        #[allow(clippy::suspicious_arithmetic_impl)]
+        fn sub(self, rhs: Self) -> Self {
+            Bit(self.0 ^ rhs.0)
+        }
+    }
+
+    impl Mul for Bit {
+        type Output = Self;
+
+        #[allow(clippy::suspicious_arithmetic_impl)]
+        fn mul(self, rhs: Self) -> Self {
+            Bit(self.0 & rhs.0)
+        }
+    }
+
+    impl Field for Bit {
+        fn zero() -> Self {
+            Bit(false)
+        }
+
+        fn one() -> Self {
+            Bit(true)
+        }
+
+        fn inverse(&self) -> Option<Self> {
+            self.0.then_some(*self)
+        }
+
+        fn order() -> BigUint {
+            BigUint::from(2u32)
+        }
+
+        fn characteristic() -> BigUint {
+            BigUint::from(2u32)
+        }
+
+        fn from_integer(n: usize) -> Self {
+            Bit(n % 2 == 1)
+        }
+
+        fn pth_root(&self) -> Self {
+            *self
+        }
+
+        fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+            Bit(rng.gen())
+        }
+    }
+
+    #[test]
+    fn factoring_over_gf2() {
+        let bits = |bits: &[u8]| Polynomial::new(bits.iter().map(|&b| Bit(b == 1)).collect());
+        let x_plus_one = bits(&[1, 1]);
+        let cubics = [bits(&[1, 1, 0, 1]), bits(&[1, 0, 1, 1])];
+
+        let x_x_plus_one = &Polynomial::x() * &x_plus_one;
+        let mut roots = x_x_plus_one.roots().unwrap();
+        roots.sort_by_key(|root| root.0);
+        assert_eq!(vec![Bit(false), Bit(true)], roots);
+
+        let f = &(&x_x_plus_one * &cubics[0]) * &cubics[1];
+        let factors = f.factor().unwrap();
+        assert_eq!(4, factors.len());
+        for factor in [Polynomial::x(), x_plus_one].into_iter().chain(cubics) {
+            assert!(factors.contains(&(factor, 1)));
+        }
+    }
+}
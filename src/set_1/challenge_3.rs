@@ -7,7 +7,7 @@ pub fn break_single_char_xor(ciphertext: &[u8]) -> u8 {
     let mut best_key = 0;
     let mut best_score = f64::MAX;
     for candidate in 0..=255u8 {
-        let plain_text = xor_repeat(&ciphertext, &[candidate]).unwrap();
+        let plain_text = xor_repeat(ciphertext, &[candidate]).unwrap();
 
         let score = score_plain_text(&plain_text);
         // println!(
@@ -9,7 +9,7 @@ pub fn break_repeated_xor(ciphertext: &[u8], min_key_size: usize, max_key_size:
 
     for ks in key_size {
         let maybe_key = find_key_of_size_n(ciphertext, ks);
-        let decrypted_text = xor_repeat(&ciphertext, &maybe_key).unwrap();
+        let decrypted_text = xor_repeat(ciphertext, &maybe_key).unwrap();
         let score = score_plain_text(&decrypted_text);
 
         if score < best_text_score {
@@ -29,7 +29,7 @@ fn find_key_of_size_n(ciphertext: &[u8], key_size: usize) -> Vec<u8> {
             .iter()
             .skip(i)
             .step_by(key_size)
-            .map(|i| *i)
+            .copied()
             .collect();
         let key_i = break_single_char_xor(&cipher_subsample);
         key.push(key_i);
@@ -75,7 +75,7 @@ pub fn hamming_distance(a: &[u8], b: &[u8]) -> Option<u32> {
     }
 
     let distance = a
-        .into_iter()
+        .iter()
         .zip(b.iter())
         .map(|(x, y)| (x ^ y).count_ones())
         .sum();
@@ -153,7 +153,7 @@ mod tests {
 
             let average = f64::from(total_distance) / f64::from(samples);
             println!("Average hamming distance with xor: {average}");
-            assert!(false);
+            panic!();
         }
 
         #[test]
@@ -173,7 +173,7 @@ mod tests {
 
             let average = f64::from(total_distance) / f64::from(samples);
             println!("Average hamming distance without xor: {average}");
-            assert!(false);
+            panic!();
         }
 
         // Provbabaly need to sample with english letter frequency to get more accurate statistics
@@ -225,7 +225,7 @@ mod tests {
                 }
             }
 
-            ' ' as u8
+            b' '
         }
     }
 }
@@ -1,33 +1,43 @@
-use aes::{
-    cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit},
-    Aes128,
+use aes::Aes128;
+
+#[cfg(not(feature = "parallel"))]
+use crate::modes::{ecb_decrypt, ecb_encrypt};
+#[cfg(feature = "parallel")]
+use crate::modes::{
+    ecb_decrypt_in_place_parallel, ecb_encrypt_in_place_parallel, DEFAULT_PARALLEL_THRESHOLD,
 };
+use crate::{block_cipher::BlockCipher, modes::ecb_encrypt_in_place};
 
 /// A very slow AES128-ECB decryptor
+///
+/// With the `parallel` feature, long cipher texts are decrypted on several threads
 pub fn aes_128_ecb_decryt(ciphertext: &[u8], key: &[u8; 16]) -> Option<Vec<u8>> {
-    // let mut out = Vec::new();
-    let exact_key = GenericArray::from(*key);
-    let cipher = Aes128::new(&exact_key);
+    let cipher = <Aes128 as BlockCipher>::new(key);
 
-    let mut decrypt_in_place = ciphertext.to_vec();
-    for block in decrypt_in_place.chunks_mut(16) {
-        cipher.decrypt_block(GenericArray::from_mut_slice(block));
+    #[cfg(feature = "parallel")]
+    {
+        let mut plain_text = ciphertext.to_vec();
+        ecb_decrypt_in_place_parallel(&cipher, &mut plain_text, DEFAULT_PARALLEL_THRESHOLD)?;
+        Some(plain_text)
     }
-
-    Some(decrypt_in_place)
+    #[cfg(not(feature = "parallel"))]
+    ecb_decrypt(&cipher, ciphertext)
 }
 
 /// A very slow AES128-ECB encryptor
+///
+/// With the `parallel` feature, long plain texts are encrypted on several threads
 pub fn aes_128_ecb_encrypt(plain_text: &[u8], key: &[u8; 16]) -> Option<Vec<u8>> {
-    let exact_key = GenericArray::from(*key);
-    let cipher = Aes128::new(&exact_key);
-    let mut encrypt_in_place = plain_text.to_vec();
+    let cipher = <Aes128 as BlockCipher>::new(key);
 
-    for block in encrypt_in_place.chunks_mut(16) {
-        cipher.encrypt_block(GenericArray::from_mut_slice(block));
+    #[cfg(feature = "parallel")]
+    {
+        let mut cipher_text = plain_text.to_vec();
+        ecb_encrypt_in_place_parallel(&cipher, &mut cipher_text, DEFAULT_PARALLEL_THRESHOLD)?;
+        Some(cipher_text)
     }
-
-    Some(encrypt_in_place)
+    #[cfg(not(feature = "parallel"))]
+    ecb_encrypt(&cipher, plain_text)
 }
 
 /// A very slow AES128-ECB encryptor
@@ -35,11 +45,7 @@ pub fn aes_128_ecb_encrypt_in_place<'a>(
     plain_text: &'a mut [u8],
     cipher: &Aes128,
 ) -> Option<&'a mut [u8]> {
-    for block in plain_text.chunks_mut(16) {
-        cipher.encrypt_block(GenericArray::from_mut_slice(block));
-    }
-
-    Some(plain_text)
+    ecb_encrypt_in_place(cipher, plain_text)
 }
 
 #[cfg(test)]
@@ -47,7 +53,7 @@ mod tests {
     use std::io::Read;
 
     use super::*;
-    use crate::set_1::{base64_to_bytes, xor_exact};
+    use crate::set_1::base64_to_bytes;
 
     #[test]
     #[ignore = "challenge 7 sample decryption"]
@@ -66,7 +72,7 @@ mod tests {
         let plain_text = aes_128_ecb_decryt(&ciphertext, key.try_into().unwrap()).unwrap();
 
         println!("{}", String::from_utf8_lossy(&plain_text));
-        assert!(false);
+        panic!();
     }
 
     #[test]
@@ -1,6 +1,6 @@
 /// Checks for repeated 16 byte code blocks
 pub fn is_aes_ecb(ciphertext: &[u8]) -> bool {
-    if ciphertext.len() % 16 > 0 {
+    if !ciphertext.len().is_multiple_of(16) {
         return false;
     }
 
@@ -12,7 +12,7 @@ pub fn is_aes_ecb(ciphertext: &[u8]) -> bool {
         }
     }
 
-    return false;
+    false
 }
 
 #[cfg(test)]
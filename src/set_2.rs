@@ -5,8 +5,12 @@ mod challenge_13;
 mod challenge_14;
 mod challenge_9;
 
-pub use challenge_10::{aes_128_cbc_decrypt_padded, aes_128_cbc_encrypt_padded};
-pub use challenge_11::{detect_encryption_mode, BlockMode};
+pub use challenge_10::{
+    aes_128_cbc_cs_decrypt, aes_128_cbc_cs_encrypt, aes_128_cbc_decrypt,
+    aes_128_cbc_decrypt_in_place, aes_128_cbc_decrypt_padded, aes_128_cbc_encrypt,
+    aes_128_cbc_encrypt_in_place, aes_128_cbc_encrypt_padded,
+};
+pub use challenge_11::{detect_encryption_mode, encryption_oracle, random_aes_key, BlockMode};
 pub use challenge_12::find_hidden_message_simple;
 pub use challenge_14::EcbProbe;
 pub use challenge_9::pkcs_7;
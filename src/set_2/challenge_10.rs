@@ -1,90 +1,95 @@
+use aes::Aes128;
+
+#[cfg(not(feature = "parallel"))]
+use crate::modes::cbc_decrypt;
+#[cfg(feature = "parallel")]
+use crate::modes::{cbc_decrypt_in_place_parallel, DEFAULT_PARALLEL_THRESHOLD};
 use crate::{
-    set_1::{aes_128_ecb_decryt, aes_128_ecb_encrypt, xor_exact},
-    set_2::pkcs_7,
+    block_cipher::BlockCipher,
+    modes::{
+        cbc_cs_decrypt, cbc_cs_encrypt, cbc_decrypt_in_place, cbc_decrypt_padded, cbc_encrypt,
+        cbc_encrypt_in_place, cbc_encrypt_padded, CiphertextStealing,
+    },
+    padding::Pkcs7,
 };
 
 pub fn aes_128_cbc_encrypt_padded(iv: &[u8; 16], plain_text: &[u8], key: [u8; 16]) -> Vec<u8> {
-    assert!(iv.len() == 16);
-
-    let padded_plain_text = pkcs_7(plain_text, 16);
-    let mut output = Vec::new();
-
-    let mut cipher_text = iv.to_vec();
-    for (block_number, chunk) in padded_plain_text.chunks(16).enumerate() {
-        // println!("({block_number}) key: {key:x?}");
-        // println!("({block_number}) previous cipher_text: {cipher_text:x?}");
-        // println!("({block_number}) chunk: {chunk:x?}");
-        let input = xor_exact(&cipher_text, chunk).unwrap();
-        // println!("({block_number}) input: {input:x?}");
-
-        cipher_text = aes_128_ecb_encrypt(&input, &key).unwrap();
-        // println!("({block_number}) cipher_text: {cipher_text:x?}");
-
-        output.extend_from_slice(&cipher_text);
-    }
-
-    output
+    cbc_encrypt_padded(&<Aes128 as BlockCipher>::new(&key), iv, plain_text, &Pkcs7).unwrap()
 }
 
 pub fn aes_128_cbc_encrypt(iv: &[u8; 16], plain_text: &[u8], key: [u8; 16]) -> Vec<u8> {
-    assert!(iv.len() == 16);
-    assert!(plain_text.len() % 16 == 0);
-    let mut output = Vec::new();
-
-    let mut cipher_text = iv.to_vec();
-    for (block_number, chunk) in plain_text.chunks(16).enumerate() {
-        // println!("({block_number}) key: {key:x?}");
-        // println!("({block_number}) previous cipher_text: {cipher_text:x?}");
-        // println!("({block_number}) chunk: {chunk:x?}");
-        let input = xor_exact(&cipher_text, chunk).unwrap();
-        // println!("({block_number}) input: {input:x?}");
-
-        cipher_text = aes_128_ecb_encrypt(&input, &key).unwrap();
-        // println!("({block_number}) cipher_text: {cipher_text:x?}");
-
-        output.extend_from_slice(&cipher_text);
-    }
-
-    output
+    cbc_encrypt(&<Aes128 as BlockCipher>::new(&key), iv, plain_text)
+        .expect("plain text must be a whole number of blocks")
 }
 
-pub fn aes_128_cbc_decrypt_padded(iv: &[u8; 16], ciphertext: &[u8], key: [u8; 16]) -> Vec<u8> {
-    assert!(iv.len() == 16);
-    assert!(ciphertext.len() % 16 == 0);
-
-    let mut output = Vec::new();
-    let mut previous_cipher_text = iv.to_vec();
-
-    for block in ciphertext.chunks(16) {
-        let almost_pt = aes_128_ecb_decryt(block, &key).unwrap();
-        let plain_text = xor_exact(&almost_pt, &previous_cipher_text).unwrap();
-        output.extend_from_slice(&plain_text);
-        previous_cipher_text = block.to_vec()
-    }
+/// Returning None indicates the cipher text is not a whole number of blocks or its PKCS#7 padding is invalid
+pub fn aes_128_cbc_decrypt_padded(
+    iv: &[u8; 16],
+    ciphertext: &[u8],
+    key: [u8; 16],
+) -> Option<Vec<u8>> {
+    cbc_decrypt_padded(&<Aes128 as BlockCipher>::new(&key), iv, ciphertext, &Pkcs7)
+}
 
-    // remove pkcs#7 padding
-    let bytes_to_remove = *output.last().unwrap();
-    for _ in 0..bytes_to_remove {
-        let _ = output.pop();
+/// With the `parallel` feature, long cipher texts are decrypted on several threads
+pub fn aes_128_cbc_decrypt(iv: &[u8; 16], ciphertext: &[u8], key: [u8; 16]) -> Vec<u8> {
+    let cipher = <Aes128 as BlockCipher>::new(&key);
+
+    #[cfg(feature = "parallel")]
+    {
+        let mut plain_text = ciphertext.to_vec();
+        cbc_decrypt_in_place_parallel(&cipher, iv, &mut plain_text, DEFAULT_PARALLEL_THRESHOLD)
+            .expect("cipher text must be a whole number of blocks");
+        plain_text
     }
+    #[cfg(not(feature = "parallel"))]
+    cbc_decrypt(&cipher, iv, ciphertext).expect("cipher text must be a whole number of blocks")
+}
 
-    output
+/// Encrypts plain text of at least one block with ciphertext stealing, the cipher text is just as long
+///
+/// Returning None indicates the plain text is shorter than a block
+pub fn aes_128_cbc_cs_encrypt(
+    iv: &[u8; 16],
+    plain_text: &[u8],
+    key: [u8; 16],
+    variant: CiphertextStealing,
+) -> Option<Vec<u8>> {
+    cbc_cs_encrypt(&<Aes128 as BlockCipher>::new(&key), iv, plain_text, variant)
 }
-pub fn aes_128_cbc_decrypt(iv: &[u8; 16], ciphertext: &[u8], key: [u8; 16]) -> Vec<u8> {
-    assert!(iv.len() == 16);
-    assert!(ciphertext.len() % 16 == 0);
 
-    let mut output = Vec::new();
-    let mut previous_cipher_text = iv.to_vec();
+/// Decrypts cipher text of at least one block with ciphertext stealing
+///
+/// Returning None indicates the cipher text is shorter than a block
+pub fn aes_128_cbc_cs_decrypt(
+    iv: &[u8; 16],
+    ciphertext: &[u8],
+    key: [u8; 16],
+    variant: CiphertextStealing,
+) -> Option<Vec<u8>> {
+    cbc_cs_decrypt(&<Aes128 as BlockCipher>::new(&key), iv, ciphertext, variant)
+}
 
-    for block in ciphertext.chunks(16) {
-        let almost_pt = aes_128_ecb_decryt(block, &key).unwrap();
-        let plain_text = xor_exact(&almost_pt, &previous_cipher_text).unwrap();
-        output.extend_from_slice(&plain_text);
-        previous_cipher_text = block.to_vec()
-    }
+/// Encrypts the buffer in place with an already expanded key
+///
+/// Returning None indicates the buffer is not a whole number of blocks
+pub fn aes_128_cbc_encrypt_in_place<'a>(
+    iv: &[u8; 16],
+    buffer: &'a mut [u8],
+    cipher: &Aes128,
+) -> Option<&'a mut [u8]> {
+    cbc_encrypt_in_place(cipher, iv, buffer)
+}
 
-    output
+/// Decrypts the buffer in place with an already expanded key
+///
+/// Returning None indicates the buffer is not a whole number of blocks
+pub fn aes_128_cbc_decrypt_in_place<'a>(
+    iv: &[u8; 16],
+    buffer: &'a mut [u8],
+    cipher: &Aes128,
+) -> Option<&'a mut [u8]> {
+    cbc_decrypt_in_place(cipher, iv, buffer)
 }
 
 #[cfg(test)]
@@ -113,7 +118,7 @@ mod tests {
 
         let plaintext = aes_128_cbc_decrypt(&iv, &cipher_text, key);
         println!("{}", String::from_utf8_lossy(&plaintext));
-        assert!(false);
+        panic!();
     }
 
     #[test]
@@ -122,11 +127,33 @@ mod tests {
         let key = [1; 16];
         let iv = [2; 16];
 
-        let cipher_text = aes_128_cbc_encrypt(&iv, &plain_text, key.clone());
+        let cipher_text = aes_128_cbc_encrypt(&iv, &plain_text, key);
         assert_ne!(cipher_text, plain_text);
 
-        let decypted_plain_text = aes_128_cbc_decrypt(&iv, &cipher_text, key.clone());
+        let decypted_plain_text = aes_128_cbc_decrypt(&iv, &cipher_text, key);
         assert_eq!(plain_text, decypted_plain_text);
+
+        let cipher = <Aes128 as BlockCipher>::new(&key);
+        let mut buffer = plain_text.clone();
+        aes_128_cbc_encrypt_in_place(&iv, &mut buffer, &cipher).unwrap();
+        assert_eq!(cipher_text, buffer);
+        aes_128_cbc_decrypt_in_place(&iv, &mut buffer, &cipher).unwrap();
+        assert_eq!(plain_text, buffer);
+    }
+
+    #[test]
+    fn ciphertext_stealing() {
+        let plain_text = b"not a whole number of blocks".to_vec();
+        let key = [1; 16];
+        let iv = [2; 16];
+
+        let cipher_text =
+            aes_128_cbc_cs_encrypt(&iv, &plain_text, key, CiphertextStealing::Cs3).unwrap();
+        assert_eq!(plain_text.len(), cipher_text.len());
+        assert_eq!(
+            Some(plain_text),
+            aes_128_cbc_cs_decrypt(&iv, &cipher_text, key, CiphertextStealing::Cs3)
+        );
     }
 
     mod aes_128_cbc_encrypt {
@@ -207,9 +234,25 @@ mod tests {
                 .unwrap();
 
             let plain_text = hex_to_bytes("6bc1bee22e409f96e93d7e117393172abb").unwrap();
-            let cipher_text = aes_128_cbc_encrypt_padded(&iv, &plain_text, key.clone());
+            let cipher_text = aes_128_cbc_encrypt_padded(&iv, &plain_text, key);
             let decrypted_plain_text = aes_128_cbc_decrypt_padded(&iv, &cipher_text, key);
-            assert_eq!(decrypted_plain_text, plain_text);
+            assert_eq!(decrypted_plain_text, Some(plain_text));
+        }
+
+        #[test]
+        fn invalid_padding() {
+            let key = [7; 16];
+            let iv = [0; 16];
+
+            // Padding bytes of 0, over 16 and ones that don't all match are all rejected
+            for last_block in [[0; 16], [17; 16], [2; 16]] {
+                let mut last_block = last_block;
+                last_block[14] = 3;
+                let cipher_text = aes_128_cbc_encrypt(&iv, &last_block, key);
+                assert_eq!(None, aes_128_cbc_decrypt_padded(&iv, &cipher_text, key));
+            }
+            assert_eq!(None, aes_128_cbc_decrypt_padded(&iv, &[], key));
+            assert_eq!(None, aes_128_cbc_decrypt_padded(&iv, &[0; 15], key));
         }
 
         #[test]
@@ -226,7 +269,7 @@ mod tests {
             let plain_text =
                 hex_to_bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51")
                     .unwrap();
-            let cipher_text = aes_128_cbc_encrypt(&iv, &plain_text, key.clone());
+            let cipher_text = aes_128_cbc_encrypt(&iv, &plain_text, key);
             let decrypted_plain_text = aes_128_cbc_decrypt(&iv, &cipher_text, key);
             assert_eq!(decrypted_plain_text, plain_text);
         }
@@ -242,7 +285,7 @@ mod tests {
                 .unwrap();
 
             let plain_text = hex_to_bytes("6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e5130c81c46a35ce411e5fbc1191a0a52ef").unwrap();
-            let cipher_text = aes_128_cbc_encrypt(&iv, &plain_text, key.clone());
+            let cipher_text = aes_128_cbc_encrypt(&iv, &plain_text, key);
             let decrypted_plain_text = aes_128_cbc_decrypt(&iv, &cipher_text, key);
             assert_eq!(decrypted_plain_text, plain_text);
         }
@@ -3,8 +3,9 @@ use std::ops::Range;
 use rand::Rng;
 
 use crate::{
+    padding::{Padding, Pkcs7},
     set_1::{aes_128_ecb_encrypt, is_aes_ecb},
-    set_2::{aes_128_cbc_encrypt_padded, pkcs_7},
+    set_2::aes_128_cbc_encrypt_padded,
 };
 
 pub fn random_aes_key() -> [u8; 16] {
@@ -22,7 +23,7 @@ pub fn encryption_oracle(plain_text: &[u8]) -> Vec<u8> {
     let cippher_text = match use_ecb {
         true => {
             println!("ecb");
-            let block_sized_text = pkcs_7(&padded_text, 16);
+            let block_sized_text = Pkcs7.pad(&padded_text, 16).unwrap();
             aes_128_ecb_encrypt(&block_sized_text, &key).unwrap()
         }
         false => {
@@ -75,6 +76,6 @@ mod tests {
             let mode = detect_encryption_mode(encryption_oracle);
             println!("detect mode: {mode:?}\n");
         }
-        assert!(false);
+        panic!();
     }
 }
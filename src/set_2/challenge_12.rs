@@ -6,11 +6,11 @@ use aes::{
 };
 
 use crate::{
-    set_1::{aes_128_ecb_encrypt, aes_128_ecb_encrypt_in_place, base64_to_bytes},
+    set_1::{aes_128_ecb_encrypt_in_place, base64_to_bytes},
     set_2::{challenge_11::random_aes_key, detect_encryption_mode, BlockMode},
 };
 
-use super::{challenge_9::pkcs_7_in_place, pkcs_7};
+use crate::padding::{Padding, Pkcs7};
 
 /// Build an ecb encryptor function that will use the same key every time.
 ///
@@ -26,7 +26,8 @@ fn build_ecb_encryptor() -> impl for<'a> Fn(&'a [u8]) -> Vec<u8> {
         let mut prefixed_hidden_message = prefix.to_vec();
         prefixed_hidden_message.extend_from_slice(&hidden_message);
 
-        pkcs_7_in_place(&mut prefixed_hidden_message, 16);
+        let padding = Pkcs7.padding(prefixed_hidden_message.len(), 16).unwrap();
+        prefixed_hidden_message.extend_from_slice(&padding);
         aes_128_ecb_encrypt_in_place(&mut prefixed_hidden_message, &cipher).unwrap();
         prefixed_hidden_message
     }
@@ -56,7 +57,6 @@ pub fn find_hidden_message_simple(a: usize) -> String {
     // println!("hidden_message_length: {padded_hidden_message_length:?}");
 
     let mut stimulus = (0..(padded_hidden_message_length + hidden_message_length))
-        .into_iter()
         .map(|_| 0u8)
         .collect::<Vec<u8>>();
 
@@ -1,6 +1,6 @@
 //! Discovery of ECB hidden message with random prefix
 
-use std::{collections::HashMap, iter::repeat};
+use std::collections::HashMap;
 
 use aes::{
     cipher::{generic_array::GenericArray, KeyInit},
@@ -9,13 +9,15 @@ use aes::{
 
 use crate::set_1::{aes_128_ecb_encrypt_in_place, base64_to_bytes};
 
-use super::{challenge_11::random_aes_key, challenge_9::pkcs_7_in_place};
+use super::challenge_11::random_aes_key;
+use crate::padding::{Padding, Pkcs7};
 
 /// Build an ecb encryptor function that will use the same key every time.
 ///
 /// A random (but constant) prefix is added before the attacker text
 ///
 /// Every new call to const_ecb_encryptor will produce a new function the encypts under a different key
+#[allow(dead_code)]
 fn build_ecb_encryptor_with_changing_rand_prefix() -> impl for<'a> Fn(&'a [u8]) -> Vec<u8> {
     let key = random_aes_key();
     let hidden_message = base64_to_bytes("Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkgaGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBqdXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUgYnkK");
@@ -26,15 +28,14 @@ fn build_ecb_encryptor_with_changing_rand_prefix() -> impl for<'a> Fn(&'a [u8])
     move |attacker_controlled| {
         let prefix_length = rand::random::<usize>() % 512;
         let prefix = (0..prefix_length)
-            .into_iter()
             .map(|_| rand::random())
             .collect::<Vec<u8>>();
 
         let mut plain_text = prefix.clone();
-        plain_text.extend_from_slice(&attacker_controlled);
+        plain_text.extend_from_slice(attacker_controlled);
         plain_text.extend_from_slice(&hidden_message);
 
-        pkcs_7_in_place(&mut plain_text, 16);
+        plain_text.extend_from_slice(&Pkcs7.padding(plain_text.len(), 16).unwrap());
         aes_128_ecb_encrypt_in_place(&mut plain_text, &cipher).unwrap();
         plain_text
     }
@@ -72,7 +73,7 @@ where
     ///   2. Build a probe map, allowing us to known how long the rand prefix (mod block size) was and gain new information on most probs  
     pub fn probe(&self, max_block_size: usize) -> Option<String> {
         let block_size = self.find_block_size(max_block_size)?;
-        let probe_map = self.build_probe_map(block_size)?;
+        let _probe_map = self.build_probe_map(block_size)?;
 
         todo!()
     }
@@ -84,12 +85,12 @@ where
     /// Returning None indicates the block size, if it exists, is less than 8 bytes or greater than 'max_block_size'
     pub fn find_block_size(&self, max_block_size: usize) -> Option<usize> {
         // Will generate repeated blocks in the cipher text
-        let sample = Vec::from_iter(repeat(0u8).take(3 * max_block_size));
+        let sample = Vec::from_iter(std::iter::repeat_n(0u8, 3 * max_block_size));
         let cipher_text = (self.encrypt_method)(&sample);
 
         for probe_block_size in 8..=1024 {
             // Ciphertext is not an integer multiple of the block length so we dont need any more checks
-            if cipher_text.len() % probe_block_size > 0 {
+            if !cipher_text.len().is_multiple_of(probe_block_size) {
                 continue;
             }
 
@@ -115,11 +116,11 @@ where
     fn build_probe_map(&self, block_size: usize) -> Option<ProbeMap> {
         // base should not contain any numbers that are going to be used to mark the beginning of the attack controlled vector
         // TODO update to work for larger block sizes
-        let base = (1..((block_size + 1) as u8)).into_iter().cycle();
+        let base = (1..((block_size + 1) as u8)).cycle();
         let base_vector = base.clone().take(block_size).collect();
 
         let mut map = HashMap::new();
-        let marker = repeat(0).take(block_size * 3).collect::<Vec<u8>>();
+        let marker = std::iter::repeat_n(0, block_size * 3).collect::<Vec<u8>>();
         for offset in 0..block_size {
             let mut offest_sample = marker.clone();
             // Note this_probe could be extended to give us a better chance of finding a new offset on every encrypt call
@@ -136,9 +137,8 @@ where
                 // Add rand previx incase we are not byte aligned or underlying prefix doesnt span a block
                 // Note random sample is at less than the length of one block
 
-                let mut this_sample: Vec<u8> = repeat(255)
-                    .take(rand::random::<usize>() % block_size)
-                    .collect();
+                let mut this_sample: Vec<u8> =
+                    std::iter::repeat_n(255, rand::random::<usize>() % block_size).collect();
                 this_sample.extend_from_slice(&offest_sample);
 
                 let cipher_text = (self.encrypt_method)(&this_sample);
@@ -179,6 +179,7 @@ where
 }
 
 #[derive(Debug)]
+#[allow(dead_code)]
 struct ProbeMap {
     /// The zero offset probe
     base: Vec<u8>,
@@ -197,7 +198,7 @@ mod tests {
         let encrypt_method = build_ecb_encryptor_with_changing_rand_prefix();
         let ecb_probe = EcbProbe::new(encrypt_method);
 
-        let block_size = ecb_probe.probe(1024).unwrap();
+        let _block_size = ecb_probe.probe(1024).unwrap();
 
         // assert_eq!(block_size, 16)
     }
@@ -1,24 +1,12 @@
 //! PKCS#7 Padding
 
-/// Applys padding to the input such that it the output is an integer multiple of the block_size
-pub fn pkcs_7(input: &[u8], block_size: u8) -> Vec<u8> {
-    let padding_byte = block_size - (input.len() % (block_size as usize)) as u8;
-    let mut output = input.to_vec();
-
-    for _ in 0..padding_byte {
-        output.push(padding_byte);
-    }
-
-    output
-}
+use crate::padding::{Padding, Pkcs7};
 
 /// Applys padding to the input such that it the output is an integer multiple of the block_size
-pub fn pkcs_7_in_place(input: &mut Vec<u8>, block_size: u8) {
-    let padding_byte = block_size - (input.len() % (block_size as usize)) as u8;
-
-    for _ in 0..padding_byte {
-        input.push(padding_byte);
-    }
+///
+/// Returning None indicates the block size is 0 or over 255, which PKCS#7's single length byte can't describe
+pub fn pkcs_7(input: &[u8], block_size: usize) -> Option<Vec<u8>> {
+    Pkcs7.pad(input, block_size)
 }
 
 #[cfg(test)]
@@ -32,7 +20,7 @@ mod tests {
         let mut expected_padded_input = input.as_bytes().to_vec();
         expected_padded_input.extend_from_slice(&[4, 4, 4, 4]);
 
-        let padded_input = pkcs_7(input.as_bytes(), block_size);
+        let padded_input = pkcs_7(input.as_bytes(), block_size).unwrap();
         assert_eq!(expected_padded_input, padded_input);
     }
 
@@ -43,7 +31,7 @@ mod tests {
         let mut expected_padded_input = input.as_bytes().to_vec();
         expected_padded_input.extend_from_slice(&[16; 16]);
 
-        let padded_input = pkcs_7(input.as_bytes(), block_size);
+        let padded_input = pkcs_7(input.as_bytes(), block_size).unwrap();
         assert_eq!(expected_padded_input, padded_input);
     }
 
@@ -54,7 +42,13 @@ mod tests {
         let mut expected_padded_input = input.as_bytes().to_vec();
         expected_padded_input.extend_from_slice(&[1]);
 
-        let padded_input = pkcs_7(input.as_bytes(), block_size);
+        let padded_input = pkcs_7(input.as_bytes(), block_size).unwrap();
         assert_eq!(expected_padded_input, padded_input);
     }
+
+    #[test]
+    fn block_size_too_long() {
+        assert_eq!(None, pkcs_7(b"YELLOW SUBMARINE", 256));
+        assert_eq!(None, pkcs_7(b"YELLOW SUBMARINE", 0));
+    }
 }
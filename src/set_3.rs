@@ -0,0 +1,3 @@
+mod challenge_18;
+
+pub use challenge_18::aes_128_ctr;
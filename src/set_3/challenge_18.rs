@@ -0,0 +1,59 @@
+//! AES-128 in CTR mode
+
+use aes::Aes128;
+
+use crate::block_cipher::BlockCipher;
+#[cfg(not(feature = "parallel"))]
+use crate::modes::ctr_apply;
+#[cfg(feature = "parallel")]
+use crate::modes::{ctr_apply_keystream_parallel, DEFAULT_PARALLEL_THRESHOLD};
+
+/// Encrypts or decrypts text with AES-128 in CTR mode
+///
+/// The counter block is the challenge's format, a 64 bit little endian nonce followed by a 64 bit little endian
+/// block count. With the `parallel` feature, long texts are processed on several threads.
+pub fn aes_128_ctr(nonce: u64, text: &[u8], key: [u8; 16]) -> Vec<u8> {
+    let cipher = <Aes128 as BlockCipher>::new(&key);
+
+    #[cfg(feature = "parallel")]
+    {
+        let mut output = text.to_vec();
+        ctr_apply_keystream_parallel(&cipher, nonce, &mut output, DEFAULT_PARALLEL_THRESHOLD)
+            .unwrap();
+        output
+    }
+    #[cfg(not(feature = "parallel"))]
+    ctr_apply(&cipher, nonce, text).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set_1::base64_to_bytes;
+
+    #[test]
+    fn challenge_sample() {
+        let cipher_text = base64_to_bytes(
+            "L77na/nrFsKvynd6HzOoG7GHTLXsTVu9qvY/2syLXzhPweyyMTJULu/6/kXX0KSvoOLSFQ==",
+        );
+        let key = *b"YELLOW SUBMARINE";
+
+        let plain_text = aes_128_ctr(0, &cipher_text, key);
+        assert_eq!(
+            "Yo, VIP Let's kick it Ice, Ice, baby Ice, Ice, baby ",
+            String::from_utf8_lossy(&plain_text)
+        );
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let plain_text = b"an odd length message that spans several blocks".to_vec();
+        let key = [1; 16];
+
+        let cipher_text = aes_128_ctr(7, &plain_text, key);
+        assert_eq!(plain_text.len(), cipher_text.len());
+        assert_ne!(plain_text, cipher_text);
+
+        assert_eq!(plain_text, aes_128_ctr(7, &cipher_text, key));
+    }
+}
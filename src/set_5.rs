@@ -0,0 +1,5 @@
+mod challenge_39;
+
+pub use challenge_39::{
+    generate_prime, to_bytes_be_padded, RsaKeyPair, RsaPrivateKey, RsaPublicKey,
+};
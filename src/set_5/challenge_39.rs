@@ -0,0 +1,213 @@
+//! Textbook RSA
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::One;
+
+/// Miller-Rabin rounds used when testing candidate primes
+const MILLER_RABIN_ROUNDS: usize = 40;
+
+/// Small primes used to cheaply reject most candidates before running Miller-Rabin
+const SMALL_PRIMES: [u32; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+/// The public half of an RSA key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsaPublicKey {
+    pub e: BigUint,
+    pub n: BigUint,
+}
+
+/// The private half of an RSA key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RsaPrivateKey {
+    pub d: BigUint,
+    pub n: BigUint,
+}
+
+/// A matching RSA public and private key
+#[derive(Debug, Clone)]
+pub struct RsaKeyPair {
+    pub public: RsaPublicKey,
+    pub private: RsaPrivateKey,
+}
+
+impl RsaPublicKey {
+    /// Raw RSA encryption, c = m^e mod n
+    pub fn encrypt(&self, message: &BigUint) -> BigUint {
+        message.modpow(&self.e, &self.n)
+    }
+
+    /// Length of the modulus in bytes
+    pub fn byte_len(&self) -> usize {
+        self.n.bits().div_ceil(8) as usize
+    }
+}
+
+impl RsaPrivateKey {
+    /// Raw RSA decryption, m = c^d mod n
+    pub fn decrypt(&self, cipher_text: &BigUint) -> BigUint {
+        cipher_text.modpow(&self.d, &self.n)
+    }
+
+    /// Length of the modulus in bytes
+    pub fn byte_len(&self) -> usize {
+        self.n.bits().div_ceil(8) as usize
+    }
+}
+
+impl RsaKeyPair {
+    /// Generates a key pair with e = 3 and a modulus of `modulus_bits` bits
+    pub fn generate(modulus_bits: u64) -> Self {
+        Self::generate_with_exponent(modulus_bits, &BigUint::from(3u32))
+    }
+
+    /// Generates a key pair with the public exponent `e` and a modulus of `modulus_bits` bits
+    ///
+    /// Primes are regenerated until e is invertible mod the totient
+    pub fn generate_with_exponent(modulus_bits: u64, e: &BigUint) -> Self {
+        let p_bits = modulus_bits / 2;
+        let q_bits = modulus_bits - p_bits;
+
+        loop {
+            let p = generate_prime(p_bits);
+            let q = generate_prime(q_bits);
+            if p == q {
+                continue;
+            }
+
+            let n = &p * &q;
+            if n.bits() != modulus_bits {
+                continue;
+            }
+
+            let et = (&p - 1u32) * (&q - 1u32);
+            let Some(d) = e.modinv(&et) else {
+                continue;
+            };
+
+            return Self {
+                public: RsaPublicKey {
+                    e: e.clone(),
+                    n: n.clone(),
+                },
+                private: RsaPrivateKey { d, n },
+            };
+        }
+    }
+}
+
+/// Generates a random prime with exactly `bits` bits
+pub fn generate_prime(bits: u64) -> BigUint {
+    assert!(bits >= 8);
+    let mut rng = rand::thread_rng();
+
+    loop {
+        let mut candidate = rng.gen_biguint(bits);
+        // Force the top bit so the prime has the full length, and the bottom bit so it is odd
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(0, true);
+
+        if is_probable_prime(&candidate) {
+            return candidate;
+        }
+    }
+}
+
+/// Big-endian bytes of `value` left padded with zeros to `len` bytes
+///
+/// Panics if `value` does not fit in `len` bytes
+pub fn to_bytes_be_padded(value: &BigUint, len: usize) -> Vec<u8> {
+    let mut out = vec![0; len];
+    if *value == BigUint::ZERO {
+        return out;
+    }
+
+    let bytes = value.to_bytes_be();
+    assert!(bytes.len() <= len);
+    out[(len - bytes.len())..].copy_from_slice(&bytes);
+    out
+}
+
+/// Miller-Rabin probabilistic primality test
+fn is_probable_prime(n: &BigUint) -> bool {
+    for small_prime in SMALL_PRIMES {
+        let small_prime = BigUint::from(small_prime);
+        if *n == small_prime {
+            return true;
+        }
+        if (n % &small_prime) == BigUint::ZERO {
+            return false;
+        }
+    }
+
+    let one = BigUint::one();
+    let n_minus_one = n - 1u32;
+    let s = n_minus_one.trailing_zeros().unwrap();
+    let d = &n_minus_one >> s;
+
+    let mut rng = rand::thread_rng();
+    'witness: for _ in 0..MILLER_RABIN_ROUNDS {
+        let a = rng.gen_biguint_range(&BigUint::from(2u32), &n_minus_one);
+        let mut x = a.modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
+        }
+
+        for _ in 1..s {
+            x = x.modpow(&BigUint::from(2u32), n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+
+        return false;
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn small_primes() {
+        let primes = [2u32, 3, 5, 7, 101, 7919, 104729];
+        for p in primes {
+            assert!(is_probable_prime(&BigUint::from(p)), "{p} is prime");
+        }
+
+        let composites = [9u32, 15, 561, 7917, 104727];
+        for c in composites {
+            assert!(!is_probable_prime(&BigUint::from(c)), "{c} is composite");
+        }
+    }
+
+    #[test]
+    fn generated_prime_has_requested_length() {
+        let p = generate_prime(128);
+        assert_eq!(p.bits(), 128);
+    }
+
+    #[test]
+    fn padded_bytes() {
+        assert_eq!(
+            vec![0, 0, 1, 2],
+            to_bytes_be_padded(&BigUint::from(258u32), 4)
+        );
+        assert_eq!(vec![0, 0], to_bytes_be_padded(&BigUint::ZERO, 2));
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let key_pair = RsaKeyPair::generate(512);
+        let message = BigUint::from_bytes_be(b"YELLOW SUBMARINE");
+
+        let cipher_text = key_pair.public.encrypt(&message);
+        assert_ne!(cipher_text, message);
+
+        let decrypted_message = key_pair.private.decrypt(&cipher_text);
+        assert_eq!(message, decrypted_message);
+    }
+}
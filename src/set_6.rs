@@ -0,0 +1,22 @@
+mod challenge_41;
+mod challenge_42;
+mod challenge_43;
+mod challenge_44;
+mod challenge_45;
+mod challenge_46;
+mod challenge_47;
+
+pub use challenge_41::{recover_unpadded_message, RsaDecryptionServer};
+pub use challenge_42::{
+    forge_pkcs1_v15_signature, pkcs1_v15_sign, pkcs1_v15_verify, HashAlgorithm, VerificationMode,
+};
+pub use challenge_43::{
+    brute_force_x_from_k, dsa_hash, dsa_key_fingerprint, recover_x_from_k, DsaChecks, DsaKeyPair,
+    DsaParameters, DsaPrivateKey, DsaPublicKey, DsaSignature,
+};
+pub use challenge_44::{recover_x_from_repeated_nonce, SignedMessage};
+pub use challenge_45::{forge_g_p_plus_one_signature, forge_g_zero_signature};
+pub use challenge_46::{recover_plain_text_from_parity, RsaParityOracle};
+pub use challenge_47::{
+    bleichenbacher_attack, pkcs1_v15_pad, pkcs1_v15_unpad, BleichenbacherResult, RsaPaddingOracle,
+};
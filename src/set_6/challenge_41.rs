@@ -0,0 +1,100 @@
+//! Unpadded message recovery oracle
+
+use std::{cell::RefCell, collections::HashSet};
+
+use num_bigint::{BigUint, RandBigInt};
+
+use crate::set_5::{RsaPrivateKey, RsaPublicKey};
+
+/// A decryption server that will only decrypt each ciphertext once
+///
+/// Ciphertexts are remembered for the lifetime of the server, a repeated request is refused
+pub struct RsaDecryptionServer {
+    private_key: RsaPrivateKey,
+    seen: RefCell<HashSet<BigUint>>,
+}
+
+impl RsaDecryptionServer {
+    /// Creates a new [RsaDecryptionServer]
+    pub fn new(private_key: RsaPrivateKey) -> Self {
+        Self {
+            private_key,
+            seen: RefCell::new(HashSet::new()),
+        }
+    }
+
+    /// Decrypts the ciphertext
+    ///
+    /// Returning None indicates the ciphertext has already been submitted
+    pub fn decrypt(&self, cipher_text: &BigUint) -> Option<BigUint> {
+        if !self.seen.borrow_mut().insert(cipher_text.clone()) {
+            return None;
+        }
+
+        Some(self.private_key.decrypt(cipher_text))
+    }
+}
+
+/// Recovers the plain text of an unpadded RSA ciphertext from a blackbox decryption oracle
+///
+/// The oracle is never asked for `cipher_text` itself, it is blinded as C' = s^e * C mod n, decrypted and then
+/// unblinded with P = P' / s mod n
+///
+/// Returning None indicates the oracle refused to decrypt the blinded ciphertext
+pub fn recover_unpadded_message<F>(
+    public_key: &RsaPublicKey,
+    cipher_text: &BigUint,
+    decryption_oracle: F,
+) -> Option<BigUint>
+where
+    F: Fn(&BigUint) -> Option<BigUint>,
+{
+    let n = &public_key.n;
+    let mut rng = rand::thread_rng();
+
+    // s must be invertible mod n, which is only a concern if we are unlucky enough to pick a factor of n
+    let (s, s_inverse) = loop {
+        let s = rng.gen_biguint_range(&BigUint::from(2u32), n);
+        if let Some(s_inverse) = s.modinv(n) {
+            break (s, s_inverse);
+        }
+    };
+
+    let blinded_cipher_text = (public_key.encrypt(&s) * cipher_text) % n;
+    let blinded_plain_text = decryption_oracle(&blinded_cipher_text)?;
+
+    Some((blinded_plain_text * s_inverse) % n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set_5::RsaKeyPair;
+
+    #[test]
+    fn server_refuses_repeated_ciphertext() {
+        let key_pair = RsaKeyPair::generate(512);
+        let server = RsaDecryptionServer::new(key_pair.private);
+        let message = BigUint::from_bytes_be(b"{time: 1356304276, social: '555-55-5555'}");
+        let cipher_text = key_pair.public.encrypt(&message);
+
+        assert_eq!(Some(message), server.decrypt(&cipher_text));
+        assert_eq!(None, server.decrypt(&cipher_text));
+    }
+
+    #[test]
+    fn attack() {
+        let key_pair = RsaKeyPair::generate(512);
+        let server = RsaDecryptionServer::new(key_pair.private);
+        let message = BigUint::from_bytes_be(b"{time: 1356304276, social: '555-55-5555'}");
+        let cipher_text = key_pair.public.encrypt(&message);
+
+        // The victim's request, after which the server will not decrypt this ciphertext again
+        server.decrypt(&cipher_text).unwrap();
+
+        let recovered_message =
+            recover_unpadded_message(&key_pair.public, &cipher_text, |c| server.decrypt(c))
+                .unwrap();
+        assert_eq!(message, recovered_message);
+    }
+}
@@ -0,0 +1,298 @@
+//! PKCS#1 v1.5 signatures and Bleichenbacher's e=3 signature forgery
+
+use num_bigint::BigUint;
+use sha1::{Digest, Sha1};
+use sha2::Sha256;
+
+use crate::set_5::{to_bytes_be_padded, RsaPrivateKey, RsaPublicKey};
+
+/// DER encoded object identifier for SHA-1 (1.3.14.3.2.26)
+const SHA_1_OID: [u8; 5] = [0x2b, 0x0e, 0x03, 0x02, 0x1a];
+/// DER encoded object identifier for SHA-256 (2.16.840.1.101.3.4.2.1)
+const SHA_256_OID: [u8; 9] = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+
+/// ASN.1 tags used in a DigestInfo
+const ASN1_SEQUENCE: u8 = 0x30;
+const ASN1_OBJECT_IDENTIFIER: u8 = 0x06;
+const ASN1_NULL: u8 = 0x05;
+const ASN1_OCTET_STRING: u8 = 0x04;
+
+/// Hash functions that can be used to build a PKCS#1 v1.5 signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    /// Hashes the message
+    pub fn digest(&self, message: &[u8]) -> Vec<u8> {
+        match self {
+            HashAlgorithm::Sha1 => Sha1::digest(message).to_vec(),
+            HashAlgorithm::Sha256 => Sha256::digest(message).to_vec(),
+        }
+    }
+
+    /// Length of the digest in bytes
+    pub fn digest_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha1 => 20,
+            HashAlgorithm::Sha256 => 32,
+        }
+    }
+
+    fn oid(&self) -> &'static [u8] {
+        match self {
+            HashAlgorithm::Sha1 => &SHA_1_OID,
+            HashAlgorithm::Sha256 => &SHA_256_OID,
+        }
+    }
+
+    /// DER encoding of the DigestInfo up to, but not including, the digest bytes
+    ///
+    /// ```text
+    /// DigestInfo ::= SEQUENCE {
+    ///     digestAlgorithm SEQUENCE { algorithm OBJECT IDENTIFIER, parameters NULL },
+    ///     digest OCTET STRING
+    /// }
+    /// ```
+    pub fn digest_info_prefix(&self) -> Vec<u8> {
+        let oid = self.oid();
+
+        let mut algorithm_identifier = vec![ASN1_OBJECT_IDENTIFIER, oid.len() as u8];
+        algorithm_identifier.extend_from_slice(oid);
+        algorithm_identifier.extend_from_slice(&[ASN1_NULL, 0]);
+
+        let digest_info_len = 2 + algorithm_identifier.len() + 2 + self.digest_len();
+
+        let mut prefix = vec![ASN1_SEQUENCE, digest_info_len as u8];
+        prefix.extend_from_slice(&[ASN1_SEQUENCE, algorithm_identifier.len() as u8]);
+        prefix.extend_from_slice(&algorithm_identifier);
+        prefix.extend_from_slice(&[ASN1_OCTET_STRING, self.digest_len() as u8]);
+        prefix
+    }
+
+    /// DER encoded DigestInfo of the message
+    pub fn digest_info(&self, message: &[u8]) -> Vec<u8> {
+        let mut digest_info = self.digest_info_prefix();
+        digest_info.extend_from_slice(&self.digest(message));
+        digest_info
+    }
+}
+
+/// How thoroughly a PKCS#1 v1.5 signature block is checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationMode {
+    /// The decrypted block must exactly match the expected encoding
+    Strict,
+    /// Parses the padding and DigestInfo but does not check that the digest runs to the end of the block
+    Sloppy,
+}
+
+/// Builds the EMSA-PKCS1-v1_5 block, 00 01 FF .. FF 00 || DigestInfo
+///
+/// Returning None indicates the key is too short for the DigestInfo
+fn encode_signature_block(hash: HashAlgorithm, message: &[u8], key_len: usize) -> Option<Vec<u8>> {
+    let digest_info = hash.digest_info(message);
+
+    // At least 8 bytes of 0xff padding are required
+    if key_len < digest_info.len() + 11 {
+        return None;
+    }
+
+    let mut block = vec![0x00, 0x01];
+    block.resize(key_len - digest_info.len() - 1, 0xff);
+    block.push(0x00);
+    block.extend_from_slice(&digest_info);
+    Some(block)
+}
+
+/// Signs the message with a PKCS#1 v1.5 signature
+///
+/// Returning None indicates the key is too short for the DigestInfo
+pub fn pkcs1_v15_sign(
+    private_key: &RsaPrivateKey,
+    hash: HashAlgorithm,
+    message: &[u8],
+) -> Option<Vec<u8>> {
+    let key_len = private_key.byte_len();
+    let block = encode_signature_block(hash, message, key_len)?;
+
+    let signature = private_key.decrypt(&BigUint::from_bytes_be(&block));
+    Some(to_bytes_be_padded(&signature, key_len))
+}
+
+/// Verifies a PKCS#1 v1.5 signature of the message
+pub fn pkcs1_v15_verify(
+    public_key: &RsaPublicKey,
+    hash: HashAlgorithm,
+    message: &[u8],
+    signature: &[u8],
+    mode: VerificationMode,
+) -> bool {
+    let key_len = public_key.byte_len();
+    if signature.len() != key_len {
+        return false;
+    }
+
+    let signature = BigUint::from_bytes_be(signature);
+    if signature >= public_key.n {
+        return false;
+    }
+    let block = to_bytes_be_padded(&public_key.encrypt(&signature), key_len);
+
+    match mode {
+        VerificationMode::Strict => {
+            encode_signature_block(hash, message, key_len).is_some_and(|expected| expected == block)
+        }
+        VerificationMode::Sloppy => sloppy_verify_block(&block, hash, message),
+    }
+}
+
+/// Walks the block the way a broken verifier would
+///
+/// Checks 00 01, skips any 0xff bytes, checks the 00 separator and DigestInfo, then compares the digest.
+/// Nothing after the digest is looked at.
+fn sloppy_verify_block(block: &[u8], hash: HashAlgorithm, message: &[u8]) -> bool {
+    if !block.starts_with(&[0x00, 0x01]) {
+        return false;
+    }
+
+    let padding_len = block[2..].iter().take_while(|b| **b == 0xff).count();
+    let rest = &block[(2 + padding_len)..];
+    if padding_len == 0 || rest.first() != Some(&0x00) {
+        return false;
+    }
+
+    let Some(digest) = rest[1..].strip_prefix(hash.digest_info_prefix().as_slice()) else {
+        return false;
+    };
+
+    digest.len() >= hash.digest_len() && digest[..hash.digest_len()] == hash.digest(message)
+}
+
+/// Forges a PKCS#1 v1.5 signature that passes [VerificationMode::Sloppy] verification
+///
+/// The forged block is 00 01 FF 00 || DigestInfo || garbage, the garbage is chosen so the block is a perfect
+/// e'th power and no private key is needed. Only works for small e (e.g. 3) and long enough keys.
+///
+/// Returning None indicates there is not enough room in the block for the garbage to absorb the root
+pub fn forge_pkcs1_v15_signature(
+    public_key: &RsaPublicKey,
+    hash: HashAlgorithm,
+    message: &[u8],
+) -> Option<Vec<u8>> {
+    let key_len = public_key.byte_len();
+    let e: u32 = public_key.e.clone().try_into().ok()?;
+
+    let mut prefix = vec![0x00, 0x01, 0xff, 0x00];
+    prefix.extend_from_slice(&hash.digest_info(message));
+    if prefix.len() >= key_len {
+        return None;
+    }
+
+    let mut lower = prefix.clone();
+    lower.resize(key_len, 0x00);
+    let mut upper = prefix;
+    upper.resize(key_len, 0xff);
+    let lower = BigUint::from_bytes_be(&lower);
+    let upper = BigUint::from_bytes_be(&upper);
+
+    // The largest root below the upper bound only works if it didn't fall out of the garbage bytes
+    let root = upper.nth_root(e);
+    if root.pow(e) < lower {
+        return None;
+    }
+
+    Some(to_bytes_be_padded(&root, key_len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_1::hex_to_bytes, set_5::RsaKeyPair};
+
+    const MESSAGE: &[u8] = b"hi mom";
+
+    #[test]
+    fn digest_info_prefixes() {
+        let sha_1_prefix = hex_to_bytes("3021300906052b0e03021a05000414").unwrap();
+        let sha_256_prefix = hex_to_bytes("3031300d060960864801650304020105000420").unwrap();
+
+        assert_eq!(sha_1_prefix, HashAlgorithm::Sha1.digest_info_prefix());
+        assert_eq!(sha_256_prefix, HashAlgorithm::Sha256.digest_info_prefix());
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let key_pair = RsaKeyPair::generate(1024);
+
+        for hash in [HashAlgorithm::Sha1, HashAlgorithm::Sha256] {
+            let signature = pkcs1_v15_sign(&key_pair.private, hash, MESSAGE).unwrap();
+
+            for mode in [VerificationMode::Strict, VerificationMode::Sloppy] {
+                assert!(pkcs1_v15_verify(
+                    &key_pair.public,
+                    hash,
+                    MESSAGE,
+                    &signature,
+                    mode
+                ));
+                assert!(!pkcs1_v15_verify(
+                    &key_pair.public,
+                    hash,
+                    b"hi dad",
+                    &signature,
+                    mode
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn forge_sha_1() {
+        let key_pair = RsaKeyPair::generate(1024);
+        let hash = HashAlgorithm::Sha1;
+
+        let forged_signature = forge_pkcs1_v15_signature(&key_pair.public, hash, MESSAGE).unwrap();
+
+        assert!(pkcs1_v15_verify(
+            &key_pair.public,
+            hash,
+            MESSAGE,
+            &forged_signature,
+            VerificationMode::Sloppy
+        ));
+        assert!(!pkcs1_v15_verify(
+            &key_pair.public,
+            hash,
+            MESSAGE,
+            &forged_signature,
+            VerificationMode::Strict
+        ));
+    }
+
+    #[test]
+    fn forge_sha_256() {
+        // A SHA-256 DigestInfo leaves too little garbage for a cube root in a 1024 bit block
+        let key_pair = RsaKeyPair::generate(2048);
+        let hash = HashAlgorithm::Sha256;
+
+        let forged_signature = forge_pkcs1_v15_signature(&key_pair.public, hash, MESSAGE).unwrap();
+
+        assert!(pkcs1_v15_verify(
+            &key_pair.public,
+            hash,
+            MESSAGE,
+            &forged_signature,
+            VerificationMode::Sloppy
+        ));
+        assert!(!pkcs1_v15_verify(
+            &key_pair.public,
+            hash,
+            MESSAGE,
+            &forged_signature,
+            VerificationMode::Strict
+        ));
+    }
+}
@@ -0,0 +1,288 @@
+//! DSA key recovery from nonce
+
+use std::ops::Range;
+
+use num_bigint::{BigUint, RandBigInt};
+use sha1::{Digest, Sha1};
+
+const CHALLENGE_P: &str = "800000000000000089e1855218a0e7dac38136ffafa72eda7859f2171e25e65eac698c1702578b07dc2a1076da241c76c62d374d8389ea5aeffd3226a0530cc565f3bf6b50929139ebeac04f48c3c84afb796d61e5a4f9a8fda812ab59494232c7d2b4deb50aa18ee9e132bfa85ac4374d7f9091abc3d015efc871a584471bb1";
+const CHALLENGE_Q: &str = "f4f47f05794b256174bba6e9b396a7707e563c5b";
+const CHALLENGE_G: &str = "5958c9d3898b224b12672c0b98e06c60df923cb8bc999d119458fef538b8fa4046c8db53039db620c094c9fa077ef389b5322a559946a71903f990f1f7e0e025e2d7f7cf494aff1a0470f5b64c36b625a097f1651fe775323556fe00b3608c887892878480e99041be601a62166ca6894bdd41a7054ec89f756ba9fc95302291";
+
+/// DSA domain parameters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsaParameters {
+    pub p: BigUint,
+    pub q: BigUint,
+    pub g: BigUint,
+}
+
+/// A DSA signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsaSignature {
+    pub r: BigUint,
+    pub s: BigUint,
+}
+
+/// The public half of a DSA key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsaPublicKey {
+    pub params: DsaParameters,
+    pub y: BigUint,
+}
+
+/// The private half of a DSA key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DsaPrivateKey {
+    pub params: DsaParameters,
+    pub x: BigUint,
+}
+
+/// Sanity checks a DSA verifier can run before doing any maths
+///
+/// Turning a check off shows whether it was the one standing between a tampered set of domain parameters and a
+/// forged signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DsaChecks {
+    /// Requires 0 < r < q and 0 < s < q, this alone blocks the g = 0 forgery
+    pub signature_range: bool,
+    /// Requires 1 < g < p, this blocks both the g = 0 and g = p + 1 forgeries
+    pub generator_range: bool,
+}
+
+impl DsaChecks {
+    /// Every check turned on
+    pub const ALL: DsaChecks = DsaChecks {
+        signature_range: true,
+        generator_range: true,
+    };
+
+    /// Every check turned off
+    pub const NONE: DsaChecks = DsaChecks {
+        signature_range: false,
+        generator_range: false,
+    };
+}
+
+/// A matching DSA public and private key
+#[derive(Debug, Clone)]
+pub struct DsaKeyPair {
+    pub public: DsaPublicKey,
+    pub private: DsaPrivateKey,
+}
+
+impl DsaParameters {
+    /// The domain parameters given in the challenge
+    pub fn challenge() -> Self {
+        Self {
+            p: hex_to_biguint(CHALLENGE_P),
+            q: hex_to_biguint(CHALLENGE_Q),
+            g: hex_to_biguint(CHALLENGE_G),
+        }
+    }
+}
+
+impl DsaKeyPair {
+    /// Generates a key pair for the domain parameters
+    pub fn generate(params: &DsaParameters) -> Self {
+        let x = rand::thread_rng().gen_biguint_range(&BigUint::from(1u32), &params.q);
+        let y = params.g.modpow(&x, &params.p);
+
+        Self {
+            public: DsaPublicKey {
+                params: params.clone(),
+                y,
+            },
+            private: DsaPrivateKey {
+                params: params.clone(),
+                x,
+            },
+        }
+    }
+}
+
+impl DsaPrivateKey {
+    /// Signs the message with a random nonce
+    pub fn sign(&self, message: &[u8]) -> DsaSignature {
+        let mut rng = rand::thread_rng();
+        loop {
+            let k = rng.gen_biguint_range(&BigUint::from(1u32), &self.params.q);
+            if let Some(signature) = self.sign_with_nonce(message, &k) {
+                return signature;
+            }
+        }
+    }
+
+    /// Signs the message with the nonce k
+    ///
+    /// Returning None indicates k produced r = 0 or s = 0 and a different nonce has to be used
+    pub fn sign_with_nonce(&self, message: &[u8], k: &BigUint) -> Option<DsaSignature> {
+        let DsaParameters { p, q, g } = &self.params;
+
+        let r = g.modpow(k, p) % q;
+        let k_inverse = k.modinv(q)?;
+        let s = (k_inverse * (dsa_hash(message) + &self.x * &r)) % q;
+
+        if r == BigUint::ZERO || s == BigUint::ZERO {
+            return None;
+        }
+
+        Some(DsaSignature { r, s })
+    }
+}
+
+impl DsaPublicKey {
+    /// Verifies a DSA signature of the message with every parameter check enabled
+    pub fn verify(&self, message: &[u8], signature: &DsaSignature) -> bool {
+        self.verify_with_checks(message, signature, DsaChecks::ALL)
+    }
+
+    /// Verifies a DSA signature of the message, only running the enabled `checks`
+    ///
+    /// The domain parameters are taken from the key as given so an attacker is free to substitute their own
+    pub fn verify_with_checks(
+        &self,
+        message: &[u8],
+        signature: &DsaSignature,
+        checks: DsaChecks,
+    ) -> bool {
+        let DsaParameters { p, q, g } = &self.params;
+        let DsaSignature { r, s } = signature;
+
+        if checks.signature_range
+            && (*r == BigUint::ZERO || r >= q || *s == BigUint::ZERO || s >= q)
+        {
+            return false;
+        }
+
+        if checks.generator_range && (*g <= BigUint::from(1u32) || g >= p) {
+            return false;
+        }
+
+        let Some(w) = s.modinv(q) else {
+            return false;
+        };
+        let u1 = (dsa_hash(message) * &w) % q;
+        let u2 = (r * &w) % q;
+        let v = ((g.modpow(&u1, p) * self.y.modpow(&u2, p)) % p) % q;
+
+        v == *r
+    }
+}
+
+/// SHA-1 of the message as an integer, H(m)
+pub fn dsa_hash(message: &[u8]) -> BigUint {
+    BigUint::from_bytes_be(&Sha1::digest(message))
+}
+
+/// SHA-1 of the lowercase hex representation of x, as used in the challenge to identify keys
+pub fn dsa_key_fingerprint(x: &BigUint) -> [u8; 20] {
+    Sha1::digest(format!("{x:x}").as_bytes()).into()
+}
+
+/// Recovers the private key x from a signature and the nonce k used to make it, x = (s * k - H(m)) / r mod q
+pub fn recover_x_from_k(
+    params: &DsaParameters,
+    message_hash: &BigUint,
+    signature: &DsaSignature,
+    k: &BigUint,
+) -> Option<BigUint> {
+    let q = &params.q;
+    let r_inverse = signature.r.modinv(q)?;
+
+    // Add q before subtracting so the difference can't underflow
+    let sk = (&signature.s * k) % q;
+    let numerator = (sk + q - (message_hash % q)) % q;
+
+    Some((numerator * r_inverse) % q)
+}
+
+/// Searches `k_range` for the nonce used to make the signature
+///
+/// Each candidate x is matched against `x_fingerprint` (see [dsa_key_fingerprint]), this is much cheaper than
+/// checking g^x = y for every k
+///
+/// Returning None indicates the nonce was not in `k_range`
+pub fn brute_force_x_from_k(
+    params: &DsaParameters,
+    message_hash: &BigUint,
+    signature: &DsaSignature,
+    k_range: Range<u32>,
+    x_fingerprint: &[u8; 20],
+) -> Option<BigUint> {
+    k_range
+        .filter_map(|k| recover_x_from_k(params, message_hash, signature, &BigUint::from(k)))
+        .find(|x| dsa_key_fingerprint(x) == *x_fingerprint)
+}
+
+/// Parses a hex string, unlike [crate::set_1::hex_to_bytes] odd length strings are allowed
+fn hex_to_biguint(hex: &str) -> BigUint {
+    BigUint::parse_bytes(hex.as_bytes(), 16).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set_1::hex_to_bytes;
+
+    #[test]
+    fn sign_and_verify() {
+        let key_pair = DsaKeyPair::generate(&DsaParameters::challenge());
+        let message = b"YELLOW SUBMARINE";
+
+        let signature = key_pair.private.sign(message);
+
+        assert!(key_pair.public.verify(message, &signature));
+        assert!(!key_pair.public.verify(b"YELLOW SUBMARINF", &signature));
+    }
+
+    #[test]
+    fn recover_x_from_known_k() {
+        let key_pair = DsaKeyPair::generate(&DsaParameters::challenge());
+        let message = b"YELLOW SUBMARINE";
+        let k = BigUint::from(0xdeadbeefu32);
+
+        let signature = key_pair.private.sign_with_nonce(message, &k).unwrap();
+        let x = recover_x_from_k(&key_pair.public.params, &dsa_hash(message), &signature, &k);
+
+        assert_eq!(Some(key_pair.private.x), x);
+    }
+
+    #[test]
+    fn challenge_sample() {
+        let params = DsaParameters::challenge();
+        let y = hex_to_biguint("84ad4719d044495496a3201c8ff484feb45b962e7302e56a392aee4abab3e4bdebf2955b4736012f21a08084056b19bcd7fee56048e004e44984e2f411788efdc837a0d2e5abb7b555039fd243ac01f0fb2ed1dec568280ce678e931868d23eb095fde9d3779191b8c0299d6e07bbb283e6633451e535c45513b2d33c99ea17");
+        let public_key = DsaPublicKey {
+            params: params.clone(),
+            y: y.clone(),
+        };
+
+        let message = b"For those that envy a MC it can be hazardous to your health\nSo be friendly, a matter of life and death, just like a etch-a-sketch\n";
+        let signature = DsaSignature {
+            r: BigUint::parse_bytes(b"548099063082341131477253921760299949438196259240", 10)
+                .unwrap(),
+            s: BigUint::parse_bytes(b"857042759984254168557880549501802188789837994940", 10)
+                .unwrap(),
+        };
+        let x_fingerprint = hex_to_bytes("0954edd5e0afe5542a4adf012611a91912a3ec16")
+            .unwrap()
+            .try_into()
+            .unwrap();
+
+        assert_eq!(
+            hex_to_biguint("d2d0714f014a9784047eaeccf956520045c45265"),
+            dsa_hash(message)
+        );
+        assert!(public_key.verify(message, &signature));
+
+        let x = brute_force_x_from_k(
+            &params,
+            &dsa_hash(message),
+            &signature,
+            0..(1 << 16),
+            &x_fingerprint,
+        )
+        .unwrap();
+        assert_eq!(y, params.g.modpow(&x, &params.p));
+    }
+}
@@ -0,0 +1,114 @@
+//! DSA nonce recovery from repeated nonce
+
+use num_bigint::BigUint;
+
+use super::challenge_43::{dsa_hash, recover_x_from_k, DsaPublicKey, DsaSignature};
+
+/// A message and the DSA signature over it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedMessage {
+    pub message: Vec<u8>,
+    pub signature: DsaSignature,
+}
+
+/// Recovers the private key from any two signatures that share a nonce
+///
+/// A shared nonce gives a shared r, for such a pair the nonce is k = (m1 - m2) / (s1 - s2) mod q. Each candidate
+/// key is checked against y so pairs that happen to collide on r without sharing k are skipped.
+///
+/// Returning None indicates no pair of signatures shares a nonce
+pub fn recover_x_from_repeated_nonce(
+    public_key: &DsaPublicKey,
+    signed_messages: &[SignedMessage],
+) -> Option<BigUint> {
+    let params = &public_key.params;
+    let q = &params.q;
+
+    for (idx, first) in signed_messages.iter().enumerate() {
+        for second in signed_messages.iter().skip(idx + 1) {
+            if first.signature.r != second.signature.r || first.signature.s == second.signature.s {
+                continue;
+            }
+
+            let m1 = dsa_hash(&first.message) % q;
+            let m2 = dsa_hash(&second.message) % q;
+            let s1 = &first.signature.s;
+            let s2 = &second.signature.s;
+
+            // Add q before subtracting so the differences can't underflow
+            let message_difference = (m1 + q - m2) % q;
+            let Some(signature_difference_inverse) = ((s1 + q - s2) % q).modinv(q) else {
+                continue;
+            };
+            let k = (message_difference * signature_difference_inverse) % q;
+
+            let Some(x) = recover_x_from_k(params, &dsa_hash(&first.message), &first.signature, &k)
+            else {
+                continue;
+            };
+
+            if params.g.modpow(&x, &params.p) == public_key.y {
+                return Some(x);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use num_bigint::RandBigInt;
+
+    use super::*;
+    use crate::set_6::{DsaKeyPair, DsaParameters};
+
+    #[test]
+    fn attack() {
+        let key_pair = DsaKeyPair::generate(&DsaParameters::challenge());
+        let repeated_k = rand::thread_rng().gen_biguint_below(&key_pair.public.params.q);
+
+        let lines = [
+            "Listen for me, you better listen for me now. ",
+            "Pure black people mon is all I mon know. ",
+            "Yeah me shoes a an tear up an' now me toes is a show a ",
+            "Where me a born in are one hell in Jamaica",
+            "Bless me like a blind Jamaican",
+        ];
+        let mut signed_messages = lines
+            .iter()
+            .map(|line| SignedMessage {
+                message: line.as_bytes().to_vec(),
+                signature: key_pair.private.sign(line.as_bytes()),
+            })
+            .collect::<Vec<SignedMessage>>();
+
+        // Two of the signatures are made with the same nonce
+        for idx in [1, 3] {
+            signed_messages[idx].signature = key_pair
+                .private
+                .sign_with_nonce(&signed_messages[idx].message, &repeated_k)
+                .unwrap();
+        }
+
+        let x = recover_x_from_repeated_nonce(&key_pair.public, &signed_messages);
+        assert_eq!(Some(key_pair.private.x), x);
+    }
+
+    #[test]
+    fn no_repeated_nonce() {
+        let key_pair = DsaKeyPair::generate(&DsaParameters::challenge());
+        let signed_messages = ["first", "second", "third"]
+            .iter()
+            .map(|line| SignedMessage {
+                message: line.as_bytes().to_vec(),
+                signature: key_pair.private.sign(line.as_bytes()),
+            })
+            .collect::<Vec<SignedMessage>>();
+
+        assert_eq!(
+            None,
+            recover_x_from_repeated_nonce(&key_pair.public, &signed_messages)
+        );
+    }
+}
@@ -0,0 +1,108 @@
+//! DSA parameter tampering
+
+use num_bigint::{BigUint, RandBigInt};
+
+use super::challenge_43::{DsaParameters, DsaPublicKey, DsaSignature};
+
+/// Forges a signature that verifies for any message under g = 0
+///
+/// Every power of g is 0, so v = 0 for any s and the verifier only needs r = 0
+pub fn forge_g_zero_signature(params: &DsaParameters) -> DsaSignature {
+    let s = rand::thread_rng().gen_biguint_range(&BigUint::from(1u32), &params.q);
+    DsaSignature {
+        r: BigUint::ZERO,
+        s,
+    }
+}
+
+/// Forges a signature that verifies for any message under g = p + 1
+///
+/// Every power of g is 1 mod p, so picking any z with r = (y^z mod p) mod q and s = r / z mod q gives u2 = z and
+/// v = y^z mod p mod q = r
+pub fn forge_g_p_plus_one_signature(public_key: &DsaPublicKey) -> DsaSignature {
+    let DsaParameters { p, q, .. } = &public_key.params;
+    let mut rng = rand::thread_rng();
+
+    loop {
+        let z = rng.gen_biguint_range(&BigUint::from(1u32), q);
+        let r = public_key.y.modpow(&z, p) % q;
+        let Some(z_inverse) = z.modinv(q) else {
+            continue;
+        };
+        let s = (&r * z_inverse) % q;
+
+        if r != BigUint::ZERO && s != BigUint::ZERO {
+            return DsaSignature { r, s };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set_6::{DsaChecks, DsaKeyPair};
+
+    const MESSAGES: [&[u8]; 2] = [b"Hello, world", b"Goodbye, world"];
+
+    #[test]
+    fn g_zero() {
+        let params = DsaParameters {
+            g: BigUint::ZERO,
+            ..DsaParameters::challenge()
+        };
+        let key_pair = DsaKeyPair::generate(&params);
+        let signature = forge_g_zero_signature(&params);
+
+        for message in MESSAGES {
+            assert!(key_pair
+                .public
+                .verify_with_checks(message, &signature, DsaChecks::NONE));
+
+            // Either check on its own is enough to stop the forgery
+            let signature_range_only = DsaChecks {
+                signature_range: true,
+                generator_range: false,
+            };
+            let generator_range_only = DsaChecks {
+                signature_range: false,
+                generator_range: true,
+            };
+            assert!(!key_pair
+                .public
+                .verify_with_checks(message, &signature, signature_range_only));
+            assert!(!key_pair
+                .public
+                .verify_with_checks(message, &signature, generator_range_only));
+            assert!(!key_pair.public.verify(message, &signature));
+        }
+    }
+
+    #[test]
+    fn g_p_plus_one() {
+        let key_pair = DsaKeyPair::generate(&DsaParameters::challenge());
+        let params = DsaParameters {
+            g: &key_pair.public.params.p + 1u32,
+            ..DsaParameters::challenge()
+        };
+        let tampered_public_key = DsaPublicKey {
+            params,
+            y: key_pair.public.y.clone(),
+        };
+        let signature = forge_g_p_plus_one_signature(&tampered_public_key);
+
+        for message in MESSAGES {
+            // The forged signature is well formed, only the generator check stops it
+            let signature_range_only = DsaChecks {
+                signature_range: true,
+                generator_range: false,
+            };
+            assert!(tampered_public_key.verify_with_checks(
+                message,
+                &signature,
+                signature_range_only
+            ));
+            assert!(!tampered_public_key.verify(message, &signature));
+            assert!(!key_pair.public.verify(message, &signature));
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! RSA parity oracle
+
+use num_bigint::BigUint;
+
+use crate::set_5::{RsaPrivateKey, RsaPublicKey};
+
+/// A decryption server that only reveals whether the plain text is even
+pub struct RsaParityOracle {
+    private_key: RsaPrivateKey,
+}
+
+impl RsaParityOracle {
+    /// Creates a new [RsaParityOracle]
+    pub fn new(private_key: RsaPrivateKey) -> Self {
+        Self { private_key }
+    }
+
+    /// Decrypts the ciphertext and returns true if the plain text is even
+    pub fn is_even(&self, cipher_text: &BigUint) -> bool {
+        !self.private_key.decrypt(cipher_text).bit(0)
+    }
+}
+
+/// Recovers the plain text of an RSA ciphertext from a blackbox parity oracle
+///
+/// Each query doubles the plain text by multiplying the ciphertext by 2^e. Since n is odd, 2m mod n is even
+/// exactly when the doubling didn't wrap around n, which halves the interval the plain text can be in. The bounds
+/// are kept as exact rationals a * n / 2^i so no precision is lost over the log2(n) queries.
+///
+/// `progress` is called with the current upper bound after every query, printing it gives the plain text
+/// "hollywood style":
+///
+/// ```no_run
+/// # use cryptopals::{set_5::RsaKeyPair, set_6::{recover_plain_text_from_parity, RsaParityOracle}};
+/// # use num_bigint::BigUint;
+/// let key_pair = RsaKeyPair::generate(1024);
+/// let cipher_text = key_pair.public.encrypt(&BigUint::from_bytes_be(b"Funky Cold Medina"));
+/// let oracle = RsaParityOracle::new(key_pair.private);
+///
+/// recover_plain_text_from_parity(&key_pair.public, &cipher_text, |c| oracle.is_even(c), |upper_bound| {
+///     println!("{}", String::from_utf8_lossy(&upper_bound.to_bytes_be()));
+/// });
+/// ```
+pub fn recover_plain_text_from_parity<F, P>(
+    public_key: &RsaPublicKey,
+    cipher_text: &BigUint,
+    parity_oracle: F,
+    mut progress: P,
+) -> BigUint
+where
+    F: Fn(&BigUint) -> bool,
+    P: FnMut(&BigUint),
+{
+    let n = &public_key.n;
+    let doubler = public_key.encrypt(&BigUint::from(2u32));
+
+    // Plain text is in [lower * n / 2^i, (lower + 1) * n / 2^i)
+    let mut lower = BigUint::ZERO;
+    let mut denominator_bits = 0;
+    let mut doubled_cipher_text = cipher_text.clone();
+
+    for _ in 0..n.bits() {
+        doubled_cipher_text = (doubled_cipher_text * &doubler) % n;
+        lower <<= 1;
+        denominator_bits += 1;
+
+        if !parity_oracle(&doubled_cipher_text) {
+            lower += 1u32;
+        }
+
+        progress(&(((&lower + 1u32) * n) >> denominator_bits));
+    }
+
+    // The interval is now narrower than 1 so the only integer in it is the ceiling of the lower bound
+    let lower_numerator = lower * n;
+    let plain_text: BigUint = &lower_numerator >> denominator_bits;
+    if &plain_text << denominator_bits == lower_numerator {
+        plain_text
+    } else {
+        plain_text + 1u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{set_1::base64_to_bytes, set_5::RsaKeyPair};
+
+    #[test]
+    fn attack() {
+        let key_pair = RsaKeyPair::generate_with_exponent(1024, &BigUint::from(65537u32));
+        let oracle = RsaParityOracle::new(key_pair.private);
+        let plain_text = base64_to_bytes("VGhhdCdzIHdoeSBJIGZvdW5kIHlvdSBkb24ndCBwbGF5IGFyb3VuZCB3aXRoIHRoZSBGdW5reSBDb2xkIE1lZGluYQ==");
+        let cipher_text = key_pair
+            .public
+            .encrypt(&BigUint::from_bytes_be(&plain_text));
+
+        let mut upper_bounds = Vec::new();
+        let recovered = recover_plain_text_from_parity(
+            &key_pair.public,
+            &cipher_text,
+            |c| oracle.is_even(c),
+            |upper_bound| upper_bounds.push(upper_bound.clone()),
+        );
+
+        assert_eq!(plain_text, recovered.to_bytes_be());
+        assert_eq!(1024, upper_bounds.len());
+        assert!(upper_bounds.windows(2).all(|pair| pair[0] >= pair[1]));
+    }
+}
@@ -0,0 +1,250 @@
+//! Bleichenbacher's PKCS#1 v1.5 padding oracle (challenges 47 and 48)
+
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::CheckedSub;
+use rand::Rng;
+
+use crate::set_5::{to_bytes_be_padded, RsaPrivateKey, RsaPublicKey};
+
+/// Pads a message for encryption with PKCS#1 v1.5, 00 02 || random non-zero bytes || 00 || message
+///
+/// Returning None indicates the message is too long for a key of `key_len` bytes
+pub fn pkcs1_v15_pad(message: &[u8], key_len: usize) -> Option<Vec<u8>> {
+    // At least 8 bytes of random padding are required
+    if message.len() + 11 > key_len {
+        return None;
+    }
+
+    let mut rng = rand::thread_rng();
+    let mut block = vec![0x00, 0x02];
+    while block.len() < key_len - message.len() - 1 {
+        block.push(rng.gen_range(1..=255));
+    }
+    block.push(0x00);
+    block.extend_from_slice(message);
+    Some(block)
+}
+
+/// Removes PKCS#1 v1.5 encryption padding
+///
+/// Returning None indicates the block is not correctly padded
+pub fn pkcs1_v15_unpad(block: &[u8]) -> Option<Vec<u8>> {
+    if !block.starts_with(&[0x00, 0x02]) {
+        return None;
+    }
+
+    let separator = block[2..].iter().position(|b| *b == 0)? + 2;
+    if separator < 10 {
+        return None;
+    }
+
+    Some(block[(separator + 1)..].to_vec())
+}
+
+/// A decryption server that only reveals whether the plain text starts with 00 02
+pub struct RsaPaddingOracle {
+    private_key: RsaPrivateKey,
+}
+
+impl RsaPaddingOracle {
+    /// Creates a new [RsaPaddingOracle]
+    pub fn new(private_key: RsaPrivateKey) -> Self {
+        Self { private_key }
+    }
+
+    /// Decrypts the ciphertext and returns true if the plain text block starts with 00 02
+    pub fn is_conforming(&self, cipher_text: &BigUint) -> bool {
+        let plain_text = self.private_key.decrypt(cipher_text);
+        let block = to_bytes_be_padded(&plain_text, self.private_key.byte_len());
+        block.starts_with(&[0x00, 0x02])
+    }
+}
+
+/// The outcome of [bleichenbacher_attack]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BleichenbacherResult {
+    /// The recovered (still padded) plain text
+    pub plain_text: BigUint,
+    /// Total number of times the oracle was queried
+    pub oracle_calls: usize,
+}
+
+/// Recovers the plain text of an RSA ciphertext from a blackbox PKCS#1 v1.5 padding oracle
+///
+/// Follows "Chosen Ciphertext Attacks Against Protocols Based on the RSA Encryption Standard PKCS #1", each
+/// conforming s narrows the set of intervals the plain text can be in until a single value remains
+pub fn bleichenbacher_attack<F>(
+    public_key: &RsaPublicKey,
+    cipher_text: &BigUint,
+    padding_oracle: F,
+) -> BleichenbacherResult
+where
+    F: Fn(&BigUint) -> bool,
+{
+    let n = &public_key.n;
+    let mut oracle_calls = 0;
+    let mut is_conforming = |s: &BigUint, c: &BigUint| {
+        oracle_calls += 1;
+        padding_oracle(&((public_key.encrypt(s) * c) % n))
+    };
+
+    let big_b = BigUint::from(1u32) << (8 * (public_key.byte_len() - 2));
+    let two_b = &big_b * 2u32;
+    let three_b = &big_b * 3u32;
+
+    // Step 1: blinding, find s0 so that c0 = c * s0^e is conforming
+    let mut rng = rand::thread_rng();
+    let mut s0 = BigUint::from(1u32);
+    while !is_conforming(&s0, cipher_text) {
+        s0 = rng.gen_biguint_range(&BigUint::from(2u32), n);
+    }
+    let c0 = (public_key.encrypt(&s0) * cipher_text) % n;
+
+    let mut intervals = vec![(two_b.clone(), &three_b - 1u32)];
+    let mut s = BigUint::ZERO;
+
+    for i in 1.. {
+        s = if i == 1 {
+            // Step 2a: start the search at n / 3B
+            let mut s = ceil_div(n, &three_b);
+            while !is_conforming(&s, &c0) {
+                s += 1u32;
+            }
+            s
+        } else if intervals.len() > 1 {
+            // Step 2b: more than one interval left, keep searching upwards
+            let mut s = s + 1u32;
+            while !is_conforming(&s, &c0) {
+                s += 1u32;
+            }
+            s
+        } else {
+            // Step 2c: a single interval, search r and s values that roughly halve it each time
+            let (a, b) = &intervals[0];
+            let mut r = ceil_div(&((b * &s - &two_b) * 2u32), n);
+            'search: loop {
+                let s_lower = ceil_div(&(&two_b + &r * n), b);
+                let s_upper = ceil_div(&(&three_b + &r * n), a);
+
+                let mut candidate = s_lower;
+                while candidate < s_upper {
+                    if is_conforming(&candidate, &c0) {
+                        break 'search candidate;
+                    }
+                    candidate += 1u32;
+                }
+                r += 1u32;
+            }
+        };
+
+        // Step 3: narrow the set of solutions
+        let mut narrowed: Vec<(BigUint, BigUint)> = Vec::new();
+        for (a, b) in &intervals {
+            let r_lower = ceil_div(
+                &(a * &s + 1u32).checked_sub(&three_b).unwrap_or_default(),
+                n,
+            );
+            let r_upper = (b * &s).checked_sub(&two_b).unwrap_or_default() / n;
+
+            let mut r = r_lower;
+            while r <= r_upper {
+                let new_a = a.max(&ceil_div(&(&two_b + &r * n), &s)).clone();
+                let new_b = b.min(&((&three_b - 1u32 + &r * n) / &s)).clone();
+                if new_a <= new_b {
+                    insert_interval(&mut narrowed, new_a, new_b);
+                }
+                r += 1u32;
+            }
+        }
+        intervals = narrowed;
+
+        // Step 4: a single value is left
+        if intervals.len() == 1 && intervals[0].0 == intervals[0].1 {
+            let s0_inverse = s0.modinv(n).unwrap();
+            let plain_text = (&intervals[0].0 * s0_inverse) % n;
+
+            return BleichenbacherResult {
+                plain_text,
+                oracle_calls,
+            };
+        }
+    }
+
+    unreachable!()
+}
+
+/// Adds [a, b] to the intervals, merging it with any it overlaps
+fn insert_interval(intervals: &mut Vec<(BigUint, BigUint)>, mut a: BigUint, mut b: BigUint) {
+    intervals.retain(|(other_a, other_b)| {
+        if *other_b < a || b < *other_a {
+            return true;
+        }
+        if *other_a < a {
+            a = other_a.clone();
+        }
+        if *other_b > b {
+            b = other_b.clone();
+        }
+        false
+    });
+    intervals.push((a, b));
+}
+
+fn ceil_div(numerator: &BigUint, denominator: &BigUint) -> BigUint {
+    (numerator + denominator - 1u32) / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::set_5::RsaKeyPair;
+
+    fn attack_with_modulus_of(modulus_bits: u64) {
+        let key_pair = RsaKeyPair::generate(modulus_bits);
+        let key_len = key_pair.public.byte_len();
+        let oracle = RsaPaddingOracle::new(key_pair.private);
+
+        let message = b"kick it, CC";
+        let padded_message = pkcs1_v15_pad(message, key_len).unwrap();
+        let cipher_text = key_pair
+            .public
+            .encrypt(&BigUint::from_bytes_be(&padded_message));
+        assert!(oracle.is_conforming(&cipher_text));
+
+        let calls = Cell::new(0);
+        let result = bleichenbacher_attack(&key_pair.public, &cipher_text, |c| {
+            calls.set(calls.get() + 1);
+            oracle.is_conforming(c)
+        });
+
+        let recovered_block = to_bytes_be_padded(&result.plain_text, key_len);
+        assert_eq!(padded_message, recovered_block);
+        assert_eq!(Some(message.to_vec()), pkcs1_v15_unpad(&recovered_block));
+        assert_eq!(calls.get(), result.oracle_calls);
+        // Usually tens of thousands, searching for the first conforming s has a long tail
+        assert!(result.oracle_calls < 1 << 24);
+    }
+
+    #[test]
+    fn pad_and_unpad() {
+        let message = b"YELLOW SUBMARINE";
+        let padded_message = pkcs1_v15_pad(message, 64).unwrap();
+
+        assert_eq!(64, padded_message.len());
+        assert!(padded_message.starts_with(&[0x00, 0x02]));
+        assert_eq!(Some(message.to_vec()), pkcs1_v15_unpad(&padded_message));
+        assert_eq!(None, pkcs1_v15_pad(message, 26));
+    }
+
+    #[test]
+    fn attack_256_bit() {
+        attack_with_modulus_of(256);
+    }
+
+    #[test]
+    fn attack_768_bit() {
+        attack_with_modulus_of(768);
+    }
+}
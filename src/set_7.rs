@@ -0,0 +1,24 @@
+mod challenge_49;
+mod challenge_50;
+mod challenge_51;
+mod challenge_52;
+mod challenge_53;
+mod challenge_54;
+
+pub use challenge_49::{
+    aes_128_cbc_mac, forge_multi_transfer_extension, forge_transfer_from, MultiTransfer, Transfer,
+    TransferClient, TransferServer,
+};
+pub use challenge_50::{cbc_mac_hash, forge_cbc_mac_hash_collision, CBC_MAC_HASH_KEY};
+pub use challenge_51::{
+    build_compression_oracle, format_request, recover_session_id, CompressionOracleCipher,
+};
+pub use challenge_52::{
+    find_block_collision, find_cascade_collision, joux_multicollision, md_pad, md_padding,
+    BlockCollision, CascadeCollision, JouxMulticollision, MdHash, MD_BLOCK_SIZE,
+};
+pub use challenge_53::{
+    build_expandable_message, find_second_preimage, find_state_collision, intermediate_states,
+    ExpandableMessage, ExpandableMessagePiece, StateCollision,
+};
+pub use challenge_54::{build_diamond_structure, forge_prediction, DiamondStructure};
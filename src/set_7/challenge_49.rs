@@ -0,0 +1,293 @@
+//! CBC-MAC message forgery
+
+use crate::{
+    padding::{Padding, Pkcs7},
+    set_1::xor_exact,
+    set_2::{aes_128_cbc_encrypt, random_aes_key},
+};
+
+/// CBC-MAC under AES-128, the last block of the CBC encryption of the PKCS#7 padded message
+///
+/// The challenge's first protocol sends a chosen IV alongside the message, the second fixes it at zero
+pub fn aes_128_cbc_mac(iv: &[u8; 16], message: &[u8], key: [u8; 16]) -> [u8; 16] {
+    let padded_message = Pkcs7.pad(message, 16).unwrap();
+    let cipher_text = aes_128_cbc_encrypt(iv, &padded_message, key);
+
+    cipher_text[(cipher_text.len() - 16)..].try_into().unwrap()
+}
+
+/// A single transfer, `from=#{from_id}&to=#{to_id}&amount=#{amount}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Transfer {
+    pub from: u32,
+    pub to: u32,
+    pub amount: u64,
+}
+
+/// A batch of transfers, `from=#{from_id}&tx_list=#{to:amount(;to:amount)*}`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultiTransfer {
+    pub from: u32,
+    pub transactions: Vec<(u32, u64)>,
+}
+
+/// The bank's API server, it shares a key with every client and trusts any request with a valid MAC
+pub struct TransferServer {
+    key: [u8; 16],
+}
+
+impl TransferServer {
+    /// Creates a new [TransferServer] with the key it shares with its clients
+    pub fn new(key: [u8; 16]) -> Self {
+        Self { key }
+    }
+
+    /// Verifies a `message || IV || MAC` request and parses the transfer
+    ///
+    /// Returning None indicates the MAC is invalid or the message could not be parsed
+    pub fn process_transfer(&self, request: &[u8]) -> Option<Transfer> {
+        if request.len() < 32 {
+            return None;
+        }
+        let (message, iv_and_mac) = request.split_at(request.len() - 32);
+        let (iv, mac) = iv_and_mac.split_at(16);
+
+        if aes_128_cbc_mac(iv.try_into().unwrap(), message, self.key) != mac {
+            return None;
+        }
+
+        let message = String::from_utf8_lossy(message);
+        let mut from = None;
+        let mut to = None;
+        let mut amount = None;
+        for pair in message.split('&') {
+            match pair.split_once('=')? {
+                ("from", value) => from = value.parse().ok(),
+                ("to", value) => to = value.parse().ok(),
+                ("amount", value) => amount = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        Some(Transfer {
+            from: from?,
+            to: to?,
+            amount: amount?,
+        })
+    }
+
+    /// Verifies a `message || MAC` request, MAC'd under a zero IV, and parses the transfers
+    ///
+    /// Like a lot of real parsers this one is lenient, transactions that can't be parsed are skipped
+    ///
+    /// Returning None indicates the MAC is invalid or the sender could not be parsed
+    pub fn process_multi_transfer(&self, request: &[u8]) -> Option<MultiTransfer> {
+        if request.len() < 16 {
+            return None;
+        }
+        let (message, mac) = request.split_at(request.len() - 16);
+
+        if aes_128_cbc_mac(&[0; 16], message, self.key) != mac {
+            return None;
+        }
+
+        let message = String::from_utf8_lossy(message);
+        let (from, tx_list) = message.strip_prefix("from=")?.split_once("&tx_list=")?;
+        let transactions = tx_list
+            .split(';')
+            .filter_map(|transaction| {
+                let (to, amount) = transaction.split_once(':')?;
+                Some((to.parse().ok()?, amount.parse().ok()?))
+            })
+            .collect();
+
+        Some(MultiTransfer {
+            from: from.parse().ok()?,
+            transactions,
+        })
+    }
+}
+
+/// The bank's web client, it will only build requests that spend from the logged in account
+pub struct TransferClient {
+    key: [u8; 16],
+    account: u32,
+}
+
+impl TransferClient {
+    /// Creates a new [TransferClient] logged in to `account`
+    pub fn new(key: [u8; 16], account: u32) -> Self {
+        Self { key, account }
+    }
+
+    /// Builds a `message || IV || MAC` request under a random IV
+    pub fn transfer(&self, to: u32, amount: u64) -> Vec<u8> {
+        let message = format!("from={}&to={to}&amount={amount}", self.account);
+        let iv = random_aes_key();
+        let mac = aes_128_cbc_mac(&iv, message.as_bytes(), self.key);
+
+        let mut request = message.into_bytes();
+        request.extend_from_slice(&iv);
+        request.extend_from_slice(&mac);
+        request
+    }
+
+    /// Builds a `message || MAC` request under a zero IV
+    pub fn multi_transfer(&self, transactions: &[(u32, u64)]) -> Vec<u8> {
+        let tx_list = transactions
+            .iter()
+            .map(|(to, amount)| format!("{to}:{amount}"))
+            .collect::<Vec<String>>()
+            .join(";");
+        let message = format!("from={}&tx_list={tx_list}", self.account);
+        let mac = aes_128_cbc_mac(&[0; 16], message.as_bytes(), self.key);
+
+        let mut request = message.into_bytes();
+        request.extend_from_slice(&mac);
+        request
+    }
+}
+
+/// Rewrites the sender of a chosen IV request to `victim`
+///
+/// The `from=` field lives in the first block, so any change to it can be cancelled out by the same change to the
+/// IV and the MAC stays valid. Account ids must have the same number of digits.
+///
+/// Returning None indicates the request is malformed or the ids have different lengths
+pub fn forge_transfer_from(request: &[u8], victim: u32) -> Option<Vec<u8>> {
+    if request.len() < 32 {
+        return None;
+    }
+    let message_len = request.len() - 32;
+
+    let from_start = b"from=".len();
+    let from_len = request[from_start..message_len]
+        .iter()
+        .position(|b| *b == b'&')?;
+    let victim = victim.to_string();
+    if victim.len() != from_len || from_start + from_len > 16 {
+        return None;
+    }
+
+    let mut forged = request.to_vec();
+    for (offset, victim_digit) in victim.bytes().enumerate() {
+        let idx = from_start + offset;
+        let difference = forged[idx] ^ victim_digit;
+        forged[idx] ^= difference;
+        forged[message_len + idx] ^= difference;
+    }
+
+    Some(forged)
+}
+
+/// Appends the attacker's own MAC'd request to a captured zero IV request
+///
+/// CBC-MAC of the captured message leaves the chain in state t1, so XORing t1 into the first block of the
+/// attacker's message continues the chain exactly as if it had started from a zero IV. The forged request is
+/// `m1 || padding(m1) || (m2[0] ^ t1) || m2[1..]` and carries the attacker's MAC t2. The first block of the
+/// attacker's message is garbled, so it should be filler that doesn't matter.
+///
+/// Returning None indicates either request is malformed
+pub fn forge_multi_transfer_extension(captured: &[u8], attacker: &[u8]) -> Option<Vec<u8>> {
+    if captured.len() < 16 || attacker.len() < 32 {
+        return None;
+    }
+    let (captured_message, captured_mac) = captured.split_at(captured.len() - 16);
+    let (attacker_message, attacker_mac) = attacker.split_at(attacker.len() - 16);
+
+    let mut forged = Pkcs7.pad(captured_message, 16).unwrap();
+    forged.extend_from_slice(&xor_exact(&attacker_message[..16], captured_mac)?);
+    forged.extend_from_slice(&attacker_message[16..]);
+    forged.extend_from_slice(attacker_mac);
+    Some(forged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ATTACKER: u32 = 3;
+    const VICTIM: u32 = 7;
+
+    #[test]
+    fn valid_requests() {
+        let key = random_aes_key();
+        let server = TransferServer::new(key);
+        let client = TransferClient::new(key, ATTACKER);
+
+        let transfer = server.process_transfer(&client.transfer(VICTIM, 100));
+        assert_eq!(
+            Some(Transfer {
+                from: ATTACKER,
+                to: VICTIM,
+                amount: 100
+            }),
+            transfer
+        );
+
+        let multi_transfer =
+            server.process_multi_transfer(&client.multi_transfer(&[(VICTIM, 100), (5, 20)]));
+        assert_eq!(
+            Some(MultiTransfer {
+                from: ATTACKER,
+                transactions: vec![(VICTIM, 100), (5, 20)]
+            }),
+            multi_transfer
+        );
+    }
+
+    #[test]
+    fn tampered_requests_are_rejected() {
+        let key = random_aes_key();
+        let server = TransferServer::new(key);
+        let client = TransferClient::new(key, ATTACKER);
+
+        let mut request = client.transfer(VICTIM, 100);
+        request[5] = b'7';
+        assert_eq!(None, server.process_transfer(&request));
+
+        let mut request = client.multi_transfer(&[(VICTIM, 100)]);
+        request[5] = b'7';
+        assert_eq!(None, server.process_multi_transfer(&request));
+    }
+
+    #[test]
+    fn forge_chosen_iv() {
+        let key = random_aes_key();
+        let server = TransferServer::new(key);
+        let attacker_client = TransferClient::new(key, ATTACKER);
+
+        let request = attacker_client.transfer(ATTACKER, 1_000_000);
+        let forged = forge_transfer_from(&request, VICTIM).unwrap();
+
+        assert_eq!(
+            Some(Transfer {
+                from: VICTIM,
+                to: ATTACKER,
+                amount: 1_000_000
+            }),
+            server.process_transfer(&forged)
+        );
+    }
+
+    #[test]
+    fn forge_length_extension() {
+        let key = random_aes_key();
+        let server = TransferServer::new(key);
+        let victim_client = TransferClient::new(key, VICTIM);
+        let attacker_client = TransferClient::new(key, ATTACKER);
+
+        let captured = victim_client.multi_transfer(&[(5, 100), (6, 250)]);
+        // "from=3&tx_list=0" is exactly one block and gets garbled, the transfer after it survives
+        let attacker = attacker_client.multi_transfer(&[(0, 0), (ATTACKER, 1_000_000)]);
+        let forged = forge_multi_transfer_extension(&captured, &attacker).unwrap();
+
+        let multi_transfer = server.process_multi_transfer(&forged).unwrap();
+        assert_eq!(VICTIM, multi_transfer.from);
+        assert_eq!((5, 100), multi_transfer.transactions[0]);
+        assert_eq!(
+            Some(&(ATTACKER, 1_000_000)),
+            multi_transfer.transactions.last()
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Hashing with CBC-MAC
+
+use crate::{
+    set_1::{aes_128_ecb_decryt, xor_exact},
+    set_2::aes_128_cbc_encrypt,
+};
+
+use super::challenge_49::aes_128_cbc_mac;
+
+/// The fixed, public, key that turns CBC-MAC into a "hash"
+pub const CBC_MAC_HASH_KEY: [u8; 16] = *b"YELLOW SUBMARINE";
+
+/// CBC-MAC under [CBC_MAC_HASH_KEY] and a zero IV
+pub fn cbc_mac_hash(message: &[u8]) -> [u8; 16] {
+    aes_128_cbc_mac(&[0; 16], message, CBC_MAC_HASH_KEY)
+}
+
+/// Builds a JavaScript snippet that starts with `prefix` and has the same [cbc_mac_hash] as `target`
+///
+/// The forgery is laid out as `prefix || spaces || comment block || glue block || target`.
+///
+/// The glue block takes the CBC chain back to zero, so the target is hashed as if it were on its own. Since the
+/// key is known the glue block is worked out backwards, glue = state ^ D(0). The comment block starts with `//`
+/// which hides the glue and the target from the interpreter, as long as the glue has no line terminators. The
+/// rest of the comment block is a counter that is bumped until that holds.
+pub fn forge_cbc_mac_hash_collision(prefix: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut forged_prefix = prefix.to_vec();
+    while !forged_prefix.len().is_multiple_of(16) {
+        forged_prefix.push(b' ');
+    }
+
+    let zero_state_input = aes_128_ecb_decryt(&[0; 16], &CBC_MAC_HASH_KEY).unwrap();
+
+    for counter in 0u64.. {
+        let comment_block = format!("//{counter:014}");
+        let mut forged = forged_prefix.clone();
+        forged.extend_from_slice(comment_block.as_bytes());
+
+        let chain = aes_128_cbc_encrypt(&[0; 16], &forged, CBC_MAC_HASH_KEY);
+        let state = &chain[(chain.len() - 16)..];
+        let glue = xor_exact(state, &zero_state_input).unwrap();
+
+        if glue.iter().any(|b| *b == b'\n' || *b == b'\r') {
+            continue;
+        }
+
+        forged.extend_from_slice(&glue);
+        forged.extend_from_slice(target);
+        return forged;
+    }
+
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::set_1::hex_to_bytes;
+
+    const TARGET: &[u8] = b"alert('MZA who was that?');\n";
+
+    #[test]
+    fn challenge_hash() {
+        let expected_hash = hex_to_bytes("296b8d7cb78a243dda4d0a61d33bbdd1").unwrap();
+        assert_eq!(expected_hash, cbc_mac_hash(TARGET));
+    }
+
+    #[test]
+    fn forge() {
+        let prefix = b"alert('Ayo, the Wu is back!');";
+        let forged = forge_cbc_mac_hash_collision(prefix, TARGET);
+
+        assert!(forged.starts_with(prefix));
+        assert!(forged.ends_with(TARGET));
+        assert_eq!(cbc_mac_hash(TARGET), cbc_mac_hash(&forged));
+
+        // Everything after the prefix is on a single commented out line
+        let hidden = &forged[32..(forged.len() - 1)];
+        assert!(hidden.starts_with(b"//"));
+        assert!(!hidden.contains(&b'\n') && !hidden.contains(&b'\r'));
+    }
+}
@@ -0,0 +1,203 @@
+//! Compression ratio side-channel attacks
+
+use std::io::Write;
+
+use flate2::{write::DeflateEncoder, Compression};
+
+use crate::{
+    set_2::{aes_128_cbc_encrypt_padded, random_aes_key},
+    set_3::aes_128_ctr,
+};
+
+/// Characters a base64 session id can be made of
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=";
+
+/// Ends the session id in the request, guessing it correctly means the whole id has been recovered
+const SESSION_ID_TERMINATOR: u8 = b'\n';
+
+/// Number of padding lengths each guess is tried with, enough to cross at least one AES block boundary
+const PADDING_SEARCH_LEN: usize = 32;
+
+/// Number of times the padding search is moved further along to break ties, limited by the 127 padding bytes
+const PADDING_WINDOWS: usize = 3;
+
+/// The known text in front of the session id
+const SESSION_ID_PREFIX: &[u8] = b"sessionid=";
+
+/// Most known text a guess is sent behind. Keeping the match DEFLATE finds the same length keeps the cost of encoding
+/// it the same, otherwise a longer match can cost more than the literal it replaces
+const MAX_STEM_LEN: usize = 16;
+
+/// How the compressed request is encrypted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompressionOracleCipher {
+    /// AES-128-CTR, the ciphertext is exactly as long as the compressed request
+    Stream,
+    /// AES-128-CBC, the ciphertext is the compressed request padded up to a whole number of blocks
+    Cbc,
+}
+
+/// Builds the HTTP request the session cookie is sent in
+pub fn format_request(session_id: &str, body: &[u8]) -> Vec<u8> {
+    let mut request = format!(
+        "POST / HTTP/1.1\nHost: hapless.com\nCookie: sessionid={session_id}\nContent-Length: {}\n",
+        body.len()
+    )
+    .into_bytes();
+    request.extend_from_slice(body);
+    request
+}
+
+/// Build a compression oracle that will hold the same session id for every request.
+///
+/// The oracle formats a request around the attacker's body, compresses it with DEFLATE, encrypts it under a fresh
+/// key (and nonce or IV) and returns only the length of the ciphertext
+pub fn build_compression_oracle(
+    session_id: &str,
+    cipher: CompressionOracleCipher,
+) -> impl for<'a> Fn(&'a [u8]) -> usize {
+    let session_id = session_id.to_string();
+
+    move |body| {
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&format_request(&session_id, body))
+            .unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let key = random_aes_key();
+        let cipher_text = match cipher {
+            CompressionOracleCipher::Stream => aes_128_ctr(rand::random(), &compressed, key),
+            CompressionOracleCipher::Cbc => {
+                aes_128_cbc_encrypt_padded(&random_aes_key(), &compressed, key)
+            }
+        };
+        cipher_text.len()
+    }
+}
+
+/// Recovers the session id from a blackbox compression oracle
+///
+/// Guesses are sent as `padding || sessionid= || known || guess`, keeping only the last [MAX_STEM_LEN] bytes before
+/// the guess. A correct guess extends DEFLATE's back reference to the real cookie so it compresses a few bits better
+/// than a wrong one. A few bits rarely change the length on their own, and for CBC they are hidden inside a block, so
+/// each guess is tried behind a run of padding lengths and the guess with the shortest total wins. The padding is
+/// made of bytes that never appear in the request, so it can't be compressed and every extra byte moves the guess
+/// closer to the next length boundary.
+///
+/// Wrong guesses whose literals happen to be cheap can tie with the correct one. The tied guesses are tried again
+/// with longer padding and, if that isn't enough, with every possible character after them, only the correct guess
+/// can extend the match by two.
+///
+/// Returning None indicates a character could not be recovered
+pub fn recover_session_id<F>(compression_oracle: F) -> Option<String>
+where
+    F: Fn(&[u8]) -> usize,
+{
+    let candidates = BASE64_ALPHABET
+        .iter()
+        .chain([SESSION_ID_TERMINATOR].iter())
+        .copied()
+        .collect::<Vec<u8>>();
+    let mut known = Vec::new();
+
+    loop {
+        let mut stem = SESSION_ID_PREFIX.to_vec();
+        stem.extend_from_slice(&known);
+        let stem = &stem[stem.len().saturating_sub(MAX_STEM_LEN)..];
+
+        let guesses = candidates.iter().map(|c| vec![*c]).collect();
+        let mut tied = best_guesses(&compression_oracle, stem, guesses);
+        if tied.len() > 1 {
+            let extensions = tied
+                .iter()
+                .flat_map(|guess| candidates.iter().map(|c| vec![guess[0], *c]))
+                .collect();
+            tied = best_guesses(&compression_oracle, stem, extensions);
+        }
+
+        // Any tied extensions have to agree on the character being recovered
+        let next = tied.first()?[0];
+        if tied.iter().any(|guess| guess[0] != next) {
+            return None;
+        }
+
+        if next == SESSION_ID_TERMINATOR {
+            return String::from_utf8(known).ok();
+        }
+        known.push(next);
+    }
+}
+
+/// The guesses with the shortest total length, moving the padding further along until there are no ties left or
+/// it runs out
+fn best_guesses<F>(compression_oracle: &F, stem: &[u8], mut guesses: Vec<Vec<u8>>) -> Vec<Vec<u8>>
+where
+    F: Fn(&[u8]) -> usize,
+{
+    for window in 0..PADDING_WINDOWS {
+        let padding_lens = (window * PADDING_SEARCH_LEN)..((window + 1) * PADDING_SEARCH_LEN);
+        let mut best_total_len = usize::MAX;
+        let mut best = Vec::new();
+
+        for guess in guesses {
+            let total_len = padding_lens
+                .clone()
+                .map(|padding_len| {
+                    let mut body = incompressible_padding(padding_len);
+                    body.extend_from_slice(stem);
+                    body.extend_from_slice(&guess);
+                    compression_oracle(&body)
+                })
+                .sum::<usize>();
+
+            if total_len < best_total_len {
+                best_total_len = total_len;
+                best.clear();
+            }
+            if total_len == best_total_len {
+                best.push(guess);
+            }
+        }
+
+        guesses = best;
+        if guesses.len() == 1 {
+            break;
+        }
+    }
+
+    guesses
+}
+
+/// Bytes that are all different and never appear in the request
+fn incompressible_padding(len: usize) -> Vec<u8> {
+    (0x80..=0xfe).take(len).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SESSION_ID: &str = "TmV2ZXIgcmV2ZWFsIHRoZSBXdS1UYW5nIFNlY3JldCE=";
+
+    #[test]
+    fn request_format() {
+        let request = format_request(SESSION_ID, b"hello");
+        assert_eq!(
+            "POST / HTTP/1.1\nHost: hapless.com\nCookie: sessionid=TmV2ZXIgcmV2ZWFsIHRoZSBXdS1UYW5nIFNlY3JldCE=\nContent-Length: 5\nhello",
+            String::from_utf8_lossy(&request)
+        );
+    }
+
+    #[test]
+    fn attack_stream() {
+        let oracle = build_compression_oracle(SESSION_ID, CompressionOracleCipher::Stream);
+        assert_eq!(Some(SESSION_ID.to_string()), recover_session_id(oracle));
+    }
+
+    #[test]
+    fn attack_cbc() {
+        let oracle = build_compression_oracle(SESSION_ID, CompressionOracleCipher::Cbc);
+        assert_eq!(Some(SESSION_ID.to_string()), recover_session_id(oracle));
+    }
+}
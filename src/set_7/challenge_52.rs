@@ -0,0 +1,328 @@
+//! Iterated hash function multicollisions
+
+use std::{
+    cell::Cell,
+    collections::{hash_map::Entry, HashMap},
+};
+
+use crate::set_1::aes_128_ecb_encrypt;
+
+/// Block size of [MdHash], a single AES block
+pub const MD_BLOCK_SIZE: usize = 16;
+
+/// The bytes the initial state of an [MdHash] is taken from
+const INITIAL_STATE: [u8; 16] =
+    *b"\x01\x23\x45\x67\x89\xab\xcd\xef\xfe\xdc\xba\x98\x76\x54\x32\x10";
+
+/// A deliberately weak Merkle-Damgård hash
+///
+/// The compression function encrypts the message block under the state, zero padded to an AES key, and keeps the
+/// first `state_len` bytes of the output. Every call to the compression function is counted so attacks can report
+/// how much work they did.
+#[derive(Debug, Clone)]
+pub struct MdHash {
+    state_len: usize,
+    compression_calls: Cell<usize>,
+}
+
+impl MdHash {
+    /// Creates a new [MdHash] with a state, and digest, of `state_len` bytes
+    pub fn new(state_len: usize) -> Self {
+        assert!((1..=16).contains(&state_len));
+
+        Self {
+            state_len,
+            compression_calls: Cell::new(0),
+        }
+    }
+
+    /// The 16 bit hash
+    pub fn cheap() -> Self {
+        Self::new(2)
+    }
+
+    /// The 24 bit hash
+    pub fn expensive() -> Self {
+        Self::new(3)
+    }
+
+    /// Length in bytes of the state and digest
+    pub fn state_len(&self) -> usize {
+        self.state_len
+    }
+
+    /// The state hashing starts from
+    pub fn initial_state(&self) -> Vec<u8> {
+        INITIAL_STATE[..self.state_len].to_vec()
+    }
+
+    /// Number of times the compression function has been called
+    pub fn compression_calls(&self) -> usize {
+        self.compression_calls.get()
+    }
+
+    /// The compression function, truncated AES-128 of the block keyed by the state
+    pub fn compress(&self, state: &[u8], block: &[u8]) -> Vec<u8> {
+        assert_eq!(self.state_len, state.len());
+        assert_eq!(MD_BLOCK_SIZE, block.len());
+        self.compression_calls.set(self.compression_calls.get() + 1);
+
+        let mut key = [0; 16];
+        key[..self.state_len].copy_from_slice(state);
+        let mut output = aes_128_ecb_encrypt(block, &key).unwrap();
+        output.truncate(self.state_len);
+        output
+    }
+
+    /// Runs the compression function over whole blocks starting from `state`, without any padding
+    pub fn compress_blocks(&self, state: &[u8], blocks: &[u8]) -> Vec<u8> {
+        assert!(blocks.len().is_multiple_of(MD_BLOCK_SIZE));
+
+        blocks
+            .chunks(MD_BLOCK_SIZE)
+            .fold(state.to_vec(), |state, block| self.compress(&state, block))
+    }
+
+    /// Hashes a message of any length, with Merkle-Damgård length padding
+    pub fn hash(&self, message: &[u8]) -> Vec<u8> {
+        self.compress_blocks(&self.initial_state(), &md_pad(message))
+    }
+}
+
+/// Pads a message to a whole number of blocks, 0x80 || zeros || 64 bit big endian length in bits
+pub fn md_pad(message: &[u8]) -> Vec<u8> {
+    let mut padded = message.to_vec();
+    padded.extend_from_slice(&md_padding(message.len()));
+    padded
+}
+
+/// The padding [md_pad] appends to a message of `message_len` bytes
+pub fn md_padding(message_len: usize) -> Vec<u8> {
+    let mut padding = vec![0x80];
+    while (message_len + padding.len()) % MD_BLOCK_SIZE != MD_BLOCK_SIZE - 8 {
+        padding.push(0);
+    }
+    padding.extend_from_slice(&((message_len as u64) * 8).to_be_bytes());
+    padding
+}
+
+/// Two different blocks that take the same state to the same next state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockCollision {
+    pub first: [u8; 16],
+    pub second: [u8; 16],
+    /// The state after either block
+    pub state: Vec<u8>,
+}
+
+/// Birthday searches for two blocks that collide from `state`
+pub fn find_block_collision(hash: &MdHash, state: &[u8]) -> BlockCollision {
+    let mut seen: HashMap<Vec<u8>, [u8; 16]> = HashMap::new();
+
+    for counter in 0u128.. {
+        let block = counter.to_be_bytes();
+        let next_state = hash.compress(state, &block);
+
+        match seen.entry(next_state) {
+            Entry::Occupied(entry) => {
+                return BlockCollision {
+                    first: *entry.get(),
+                    second: block,
+                    state: entry.key().clone(),
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(block);
+            }
+        }
+    }
+
+    unreachable!()
+}
+
+/// A chain of block collisions, every path through it is a message with the same hash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JouxMulticollision {
+    /// One collision per block of the messages
+    pub collisions: Vec<BlockCollision>,
+    /// The compression function calls it took to find
+    pub compression_calls: usize,
+}
+
+impl JouxMulticollision {
+    /// The state every message ends in
+    pub fn final_state(&self) -> Option<&[u8]> {
+        self.collisions
+            .last()
+            .map(|collision| collision.state.as_slice())
+    }
+
+    /// Number of colliding messages, 2^n for n collisions
+    pub fn message_count(&self) -> u128 {
+        1 << self.collisions.len()
+    }
+
+    /// The colliding message picked by the bits of `index`
+    pub fn message(&self, index: u128) -> Vec<u8> {
+        self.collisions
+            .iter()
+            .enumerate()
+            .flat_map(|(i, collision)| {
+                if (index >> i) & 1 == 0 {
+                    collision.first
+                } else {
+                    collision.second
+                }
+            })
+            .collect()
+    }
+
+    /// Every colliding message
+    pub fn messages(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        (0..self.message_count()).map(|index| self.message(index))
+    }
+}
+
+/// Joux's multicollisions, `n` birthday searches chained from `state` give 2^n colliding messages
+pub fn joux_multicollision(hash: &MdHash, state: &[u8], n: usize) -> JouxMulticollision {
+    let calls_before = hash.compression_calls();
+    let mut multicollision = JouxMulticollision {
+        collisions: Vec::with_capacity(n),
+        compression_calls: 0,
+    };
+
+    let mut state = state.to_vec();
+    for _ in 0..n {
+        let collision = find_block_collision(hash, &state);
+        state = collision.state.clone();
+        multicollision.collisions.push(collision);
+    }
+
+    multicollision.compression_calls = hash.compression_calls() - calls_before;
+    multicollision
+}
+
+/// The outcome of [find_cascade_collision]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CascadeCollision {
+    /// Two different messages with the same `f(m) || g(m)`
+    pub messages: (Vec<u8>, Vec<u8>),
+    /// Number of blocks, and birthday searches, in the multicollision the messages came from
+    pub multicollision_len: usize,
+    /// Compression function calls of the cheap hash, building the multicollision
+    pub cheap_calls: usize,
+    /// Compression function calls of the expensive hash, searching the multicollision
+    pub expensive_calls: usize,
+}
+
+/// Finds a collision in `f(m) || g(m)`, where `f` is the cheap hash and `g` the expensive one
+///
+/// A multicollision of the cheap hash with 2^(b2/2) messages is likely to contain a collision in the expensive
+/// hash, b2 being its size in bits. The messages are walked through the expensive hash a block at a time, they all
+/// have the same length so any two paths that meet at a state collide. If none have met by the end the
+/// multicollision is grown by another block, which doubles the number of messages.
+pub fn find_cascade_collision(cheap: &MdHash, expensive: &MdHash) -> CascadeCollision {
+    let cheap_calls_before = cheap.compression_calls();
+    let expensive_calls_before = expensive.compression_calls();
+
+    let mut multicollision =
+        joux_multicollision(cheap, &cheap.initial_state(), expensive.state_len() * 8 / 2);
+
+    // Every path through the multicollision so far, by the expensive state it ends in
+    let mut paths: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+    paths.insert(expensive.initial_state(), Vec::new());
+
+    for depth in 0.. {
+        if depth == multicollision.collisions.len() {
+            let final_state = multicollision.final_state().unwrap().to_vec();
+            multicollision
+                .collisions
+                .push(find_block_collision(cheap, &final_state));
+        }
+        let collision = &multicollision.collisions[depth];
+
+        let mut next_paths: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+        for (state, path) in &paths {
+            for block in [collision.first, collision.second] {
+                let mut next_path = path.clone();
+                next_path.extend_from_slice(&block);
+
+                match next_paths.entry(expensive.compress(state, &block)) {
+                    Entry::Occupied(entry) => {
+                        // The rest of the multicollision doesn't matter, both take the first blocks
+                        let mut first = entry.get().clone();
+                        for collision in &multicollision.collisions[(depth + 1)..] {
+                            first.extend_from_slice(&collision.first);
+                            next_path.extend_from_slice(&collision.first);
+                        }
+
+                        return CascadeCollision {
+                            messages: (first, next_path),
+                            multicollision_len: multicollision.collisions.len(),
+                            cheap_calls: cheap.compression_calls() - cheap_calls_before,
+                            expensive_calls: expensive.compression_calls() - expensive_calls_before,
+                        };
+                    }
+                    Entry::Vacant(entry) => {
+                        entry.insert(next_path);
+                    }
+                }
+            }
+        }
+        paths = next_paths;
+    }
+
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn padding() {
+        assert_eq!(16, md_pad(b"").len());
+        assert_eq!(16, md_pad(b"YELLOW ").len());
+        assert_eq!(32, md_pad(b"YELLOW S").len());
+        assert_eq!(32, md_pad(b"YELLOW SUBMARINE").len());
+        assert_eq!(
+            [0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x80],
+            md_pad(b"YELLOW SUBMARINE")[16..]
+        );
+    }
+
+    #[test]
+    fn multicollision() {
+        let hash = MdHash::cheap();
+        let multicollision = joux_multicollision(&hash, &hash.initial_state(), 4);
+        assert_eq!(hash.compression_calls(), multicollision.compression_calls);
+
+        let messages = multicollision.messages().collect::<Vec<Vec<u8>>>();
+        assert_eq!(16, messages.len());
+
+        let digest = hash.hash(&messages[0]);
+        for (i, message) in messages.iter().enumerate() {
+            assert_eq!(digest, hash.hash(message));
+            assert!(!messages[..i].contains(message));
+        }
+    }
+
+    #[test]
+    fn cascade_collision() {
+        let cheap = MdHash::cheap();
+        let expensive = MdHash::expensive();
+
+        let collision = find_cascade_collision(&cheap, &expensive);
+        assert_eq!(cheap.compression_calls(), collision.cheap_calls);
+        assert_eq!(expensive.compression_calls(), collision.expensive_calls);
+        // 2^12 messages are likely to collide in the 24 bit hash, and each is walked through it a block at a time
+        assert!(collision.multicollision_len >= 12);
+        assert!(collision.expensive_calls < 1 << (collision.multicollision_len + 2));
+
+        let (first, second) = &collision.messages;
+        assert_ne!(first, second);
+        assert_eq!(first.len(), second.len());
+        assert_eq!(cheap.hash(first), cheap.hash(second));
+        assert_eq!(expensive.hash(first), expensive.hash(second));
+    }
+}
@@ -0,0 +1,221 @@
+//! Kelsey and Schneier's expandable messages
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use super::challenge_52::{MdHash, MD_BLOCK_SIZE};
+
+/// Two blocks that take two different states to the same next state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StateCollision {
+    /// The block after the first state
+    pub first: [u8; 16],
+    /// The block after the second state
+    pub second: [u8; 16],
+    /// The state after either block
+    pub state: Vec<u8>,
+}
+
+/// Birthday searches for a block from `first_state` and a block from `second_state` that lead to the same state
+pub fn find_state_collision(
+    hash: &MdHash,
+    first_state: &[u8],
+    second_state: &[u8],
+) -> StateCollision {
+    let mut first_seen: HashMap<Vec<u8>, [u8; 16]> = HashMap::new();
+    let mut second_seen: HashMap<Vec<u8>, [u8; 16]> = HashMap::new();
+
+    for counter in 0u128.. {
+        let block = counter.to_be_bytes();
+
+        let next_state = hash.compress(first_state, &block);
+        if let Some(second) = second_seen.get(&next_state) {
+            return StateCollision {
+                first: block,
+                second: *second,
+                state: next_state,
+            };
+        }
+        first_seen.entry(next_state).or_insert(block);
+
+        let next_state = hash.compress(second_state, &block);
+        if let Some(first) = first_seen.get(&next_state) {
+            return StateCollision {
+                first: *first,
+                second: block,
+                state: next_state,
+            };
+        }
+        second_seen.entry(next_state).or_insert(block);
+    }
+
+    unreachable!()
+}
+
+/// One choice in an [ExpandableMessage], a single block or a longer message with the same hash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandableMessagePiece {
+    pub short: [u8; 16],
+    /// 2^i dummy blocks followed by a block that collides with `short`
+    pub long: Vec<u8>,
+}
+
+/// A set of messages with every length from k to k + 2^k - 1 blocks, all ending in the same state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandableMessage {
+    /// From the longest long message, 2^(k-1) + 1 blocks, down to the shortest, 2 blocks
+    pub pieces: Vec<ExpandableMessagePiece>,
+    /// The state every message ends in
+    pub final_state: Vec<u8>,
+}
+
+impl ExpandableMessage {
+    /// The shortest message in blocks, k
+    pub fn min_blocks(&self) -> usize {
+        self.pieces.len()
+    }
+
+    /// The longest message in blocks, k + 2^k - 1
+    pub fn max_blocks(&self) -> usize {
+        self.pieces.len() + (1 << self.pieces.len()) - 1
+    }
+
+    /// Builds the message of `blocks` blocks
+    ///
+    /// Returning None indicates no message has that length
+    pub fn message(&self, blocks: usize) -> Option<Vec<u8>> {
+        if blocks < self.min_blocks() || blocks > self.max_blocks() {
+            return None;
+        }
+
+        // Each long message adds 2^i blocks over the short one, the extra length picks them bit by bit
+        let extra_blocks = blocks - self.min_blocks();
+        let mut message = Vec::with_capacity(blocks * MD_BLOCK_SIZE);
+        for (i, piece) in self.pieces.iter().enumerate() {
+            let bit = self.pieces.len() - 1 - i;
+            if (extra_blocks >> bit) & 1 == 1 {
+                message.extend_from_slice(&piece.long);
+            } else {
+                message.extend_from_slice(&piece.short);
+            }
+        }
+
+        Some(message)
+    }
+}
+
+/// Builds a (k, k + 2^k - 1) expandable message starting from `state`
+///
+/// Each of the k pieces is a collision between a single block and 2^(k-1), 2^(k-2) and so on dummy blocks followed
+/// by one more block. The dummy blocks are hashed first, then the two ends are birthday searched.
+pub fn build_expandable_message(hash: &MdHash, state: &[u8], k: usize) -> ExpandableMessage {
+    let mut pieces = Vec::with_capacity(k);
+    let mut state = state.to_vec();
+
+    for i in (0..k).rev() {
+        let dummy_blocks = vec![0; (1 << i) * MD_BLOCK_SIZE];
+        let dummy_state = hash.compress_blocks(&state, &dummy_blocks);
+
+        let collision = find_state_collision(hash, &state, &dummy_state);
+        let mut long = dummy_blocks;
+        long.extend_from_slice(&collision.second);
+
+        pieces.push(ExpandableMessagePiece {
+            short: collision.first,
+            long,
+        });
+        state = collision.state;
+    }
+
+    ExpandableMessage {
+        pieces,
+        final_state: state,
+    }
+}
+
+/// Maps the state after every whole block of the message to the number of blocks hashed to reach it
+///
+/// The first time a state is reached is kept if it repeats
+pub fn intermediate_states(hash: &MdHash, message: &[u8]) -> HashMap<Vec<u8>, usize> {
+    let mut states = HashMap::new();
+    let mut state = hash.initial_state();
+
+    for (i, block) in message.chunks_exact(MD_BLOCK_SIZE).enumerate() {
+        state = hash.compress(&state, block);
+        if let Entry::Vacant(entry) = states.entry(state.clone()) {
+            entry.insert(i + 1);
+        }
+    }
+
+    states
+}
+
+/// Finds a second preimage of a long message, a different message with the same length and hash
+///
+/// An expandable message is built from the initial state, then a bridge block is searched for that takes its final
+/// state to one of the target's intermediate states, after `i` blocks. The expandable message is cut to i - 1
+/// blocks and the rest of the target follows the bridge, so the length, and with it the padding, is unchanged.
+///
+/// Returning None indicates the target has no intermediate state an expandable message can reach
+pub fn find_second_preimage(hash: &MdHash, target: &[u8], k: usize) -> Option<Vec<u8>> {
+    let expandable_message = build_expandable_message(hash, &hash.initial_state(), k);
+
+    let reachable_blocks =
+        (expandable_message.min_blocks() + 1)..=(expandable_message.max_blocks() + 1);
+    let mut states = intermediate_states(hash, target);
+    states.retain(|_, blocks| reachable_blocks.contains(blocks));
+    if states.is_empty() {
+        return None;
+    }
+
+    let (bridge, blocks) = (0u128..).find_map(|counter| {
+        let bridge = counter.to_be_bytes();
+        let next_state = hash.compress(&expandable_message.final_state, &bridge);
+        states.get(&next_state).map(|blocks| (bridge, *blocks))
+    })?;
+
+    let mut second_preimage = expandable_message.message(blocks - 1)?;
+    second_preimage.extend_from_slice(&bridge);
+    second_preimage.extend_from_slice(&target[(blocks * MD_BLOCK_SIZE)..]);
+    Some(second_preimage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expandable_message() {
+        let hash = MdHash::cheap();
+        let expandable_message = build_expandable_message(&hash, &hash.initial_state(), 4);
+        assert_eq!(4, expandable_message.min_blocks());
+        assert_eq!(19, expandable_message.max_blocks());
+
+        for blocks in 4..=19 {
+            let message = expandable_message.message(blocks).unwrap();
+            assert_eq!(blocks * MD_BLOCK_SIZE, message.len());
+            assert_eq!(
+                expandable_message.final_state,
+                hash.compress_blocks(&hash.initial_state(), &message)
+            );
+        }
+        assert_eq!(None, expandable_message.message(3));
+        assert_eq!(None, expandable_message.message(20));
+    }
+
+    #[test]
+    fn second_preimage() {
+        let k = 10;
+        let hash = MdHash::expensive();
+        let target = (0..(1usize << k) * MD_BLOCK_SIZE)
+            .map(|i| (i * 7 + i / 256) as u8)
+            .collect::<Vec<u8>>();
+
+        let second_preimage = find_second_preimage(&hash, &target, k).unwrap();
+        // About k * 2^(b/2) for the expandable message and 2^(b-k) for the bridge, against 2^b by brute force
+        assert!(hash.compression_calls() < 1 << 17);
+
+        assert_ne!(target, second_preimage);
+        assert_eq!(target.len(), second_preimage.len());
+        assert_eq!(hash.hash(&target), hash.hash(&second_preimage));
+    }
+}
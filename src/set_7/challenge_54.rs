@@ -0,0 +1,229 @@
+//! Kelsey and Kohno's "Nostradamus" herding attack
+
+use std::collections::HashMap;
+
+use super::{
+    challenge_52::{md_padding, MdHash, MD_BLOCK_SIZE},
+    challenge_53::find_state_collision,
+};
+
+/// A binary tree of collisions that herds 2^k leaf states into a single root state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiamondStructure {
+    state_len: usize,
+    /// The 2^k states a linking block can land on
+    pub leaves: Vec<Vec<u8>>,
+    /// `blocks[i][j]` takes node j of level i to node j / 2 of level i + 1, level 0 being the leaves
+    pub blocks: Vec<Vec<[u8; 16]>>,
+    /// The state every path through the diamond ends in
+    pub root: Vec<u8>,
+}
+
+impl DiamondStructure {
+    /// Number of levels in the diamond, and blocks in every path through it
+    pub fn k(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// The blocks that take a leaf to the root
+    pub fn path_from_leaf(&self, leaf: usize) -> Vec<u8> {
+        self.blocks
+            .iter()
+            .enumerate()
+            .flat_map(|(level, blocks)| blocks[leaf >> level])
+            .collect()
+    }
+
+    /// The hash to commit to, for messages whose prefix is padded to `prefix_blocks` blocks
+    ///
+    /// Messages are `prefix || linking block || path through the diamond`, their length is fixed by the number of
+    /// prefix blocks and with it the final padding block.
+    pub fn prediction(&self, hash: &MdHash, prefix_blocks: usize) -> Vec<u8> {
+        let message_len = (prefix_blocks + 1 + self.k()) * MD_BLOCK_SIZE;
+        hash.compress_blocks(&self.root, &md_padding(message_len))
+    }
+
+    /// Serializes the diamond, `state_len || k || leaves || blocks, level by level || root`
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.state_len as u8, self.k() as u8];
+        bytes.extend(self.leaves.iter().flatten());
+        bytes.extend(self.blocks.iter().flatten().flatten());
+        bytes.extend_from_slice(&self.root);
+        bytes
+    }
+
+    /// Deserializes a diamond written by [DiamondStructure::to_bytes]
+    ///
+    /// Returning None indicates the bytes are not a serialized diamond
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (&state_len, bytes) = bytes.split_first()?;
+        let (&k, mut bytes) = bytes.split_first()?;
+        let (state_len, k) = (state_len as usize, k as usize);
+        // Also keeps the sizes below from overflowing
+        if !(1..=16).contains(&state_len) || k > state_len * 8 || k >= 32 {
+            return None;
+        }
+
+        let mut take = |len: usize| {
+            if bytes.len() < len {
+                return None;
+            }
+            let (taken, rest) = bytes.split_at(len);
+            bytes = rest;
+            Some(taken)
+        };
+
+        let leaves = take(state_len << k)?
+            .chunks(state_len)
+            .map(|leaf| leaf.to_vec())
+            .collect();
+        let blocks = (0..k)
+            .map(|level| {
+                let level_blocks = take((MD_BLOCK_SIZE << k) >> level)?
+                    .chunks(MD_BLOCK_SIZE)
+                    .map(|block| block.try_into().unwrap())
+                    .collect();
+                Some(level_blocks)
+            })
+            .collect::<Option<Vec<Vec<[u8; 16]>>>>()?;
+        let root = take(state_len)?.to_vec();
+
+        if !bytes.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            state_len,
+            leaves,
+            blocks,
+            root,
+        })
+    }
+}
+
+/// Builds a diamond structure with 2^k leaves
+///
+/// The leaves are just 2^k different states. Each level pairs up its states and birthday searches a block for each
+/// that takes both to the same state, halving the number of states until only the root is left.
+pub fn build_diamond_structure(hash: &MdHash, k: usize) -> DiamondStructure {
+    let state_len = hash.state_len();
+    assert!(k <= state_len * 8, "not enough states for 2^{k} leaves");
+
+    let leaves = (0u128..(1 << k))
+        .map(|leaf| leaf.to_be_bytes()[(16 - state_len)..].to_vec())
+        .collect::<Vec<Vec<u8>>>();
+
+    let mut states = leaves.clone();
+    let mut blocks = Vec::with_capacity(k);
+    while states.len() > 1 {
+        let mut level_blocks = Vec::with_capacity(states.len());
+        let mut next_states = Vec::with_capacity(states.len() / 2);
+
+        for pair in states.chunks(2) {
+            let collision = find_state_collision(hash, &pair[0], &pair[1]);
+            level_blocks.push(collision.first);
+            level_blocks.push(collision.second);
+            next_states.push(collision.state);
+        }
+
+        blocks.push(level_blocks);
+        states = next_states;
+    }
+
+    DiamondStructure {
+        state_len,
+        leaves,
+        blocks,
+        root: states.remove(0),
+    }
+}
+
+/// Builds a message that starts with `prefix` and hashes to the diamond's prediction for `prefix_blocks` blocks
+///
+/// The prefix is padded with spaces to `prefix_blocks` blocks, then a linking block is searched for that takes the
+/// state after it to any leaf of the diamond. The path from that leaf leads to the root.
+///
+/// Returning None indicates the prefix doesn't fit in `prefix_blocks` blocks
+pub fn forge_prediction(
+    hash: &MdHash,
+    diamond: &DiamondStructure,
+    prefix: &[u8],
+    prefix_blocks: usize,
+) -> Option<Vec<u8>> {
+    if prefix.len() > prefix_blocks * MD_BLOCK_SIZE {
+        return None;
+    }
+    let mut message = prefix.to_vec();
+    message.resize(prefix_blocks * MD_BLOCK_SIZE, b' ');
+
+    let leaves = diamond
+        .leaves
+        .iter()
+        .enumerate()
+        .map(|(i, leaf)| (leaf.as_slice(), i))
+        .collect::<HashMap<&[u8], usize>>();
+    let state = hash.compress_blocks(&hash.initial_state(), &message);
+
+    let (linking_block, leaf) = (0u128..).find_map(|counter| {
+        let linking_block = counter.to_be_bytes();
+        let next_state = hash.compress(&state, &linking_block);
+        leaves
+            .get(next_state.as_slice())
+            .map(|leaf| (linking_block, *leaf))
+    })?;
+
+    message.extend_from_slice(&linking_block);
+    message.extend_from_slice(&diamond.path_from_leaf(leaf));
+    Some(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PREFIX_BLOCKS: usize = 8;
+
+    #[test]
+    fn serialize() {
+        let hash = MdHash::cheap();
+        let diamond = build_diamond_structure(&hash, 4);
+
+        let bytes = diamond.to_bytes();
+        assert_eq!(Some(diamond), DiamondStructure::from_bytes(&bytes));
+        assert_eq!(
+            None,
+            DiamondStructure::from_bytes(&bytes[..(bytes.len() - 1)])
+        );
+        assert_eq!(
+            None,
+            DiamondStructure::from_bytes(&[bytes, vec![0]].concat())
+        );
+    }
+
+    #[test]
+    fn herd_prediction() {
+        let hash = MdHash::expensive();
+        let diamond = build_diamond_structure(&hash, 8);
+        for leaf in 0..diamond.leaves.len() {
+            assert_eq!(
+                diamond.root,
+                hash.compress_blocks(&diamond.leaves[leaf], &diamond.path_from_leaf(leaf))
+            );
+        }
+
+        // Built once before the season, reused after it
+        let diamond = DiamondStructure::from_bytes(&diamond.to_bytes()).unwrap();
+        let prediction = diamond.prediction(&hash, PREFIX_BLOCKS);
+
+        let results =
+            b"Red Sox 4, Yankees 2\nCubs 7, Mets 1\nDodgers 3, Giants 0\nAstros 5, Rangers 4\n";
+        let message = forge_prediction(&hash, &diamond, results, PREFIX_BLOCKS).unwrap();
+
+        assert!(message.starts_with(results));
+        assert_eq!(prediction, hash.hash(&message));
+        assert_eq!(
+            None,
+            forge_prediction(&hash, &diamond, &[0; 129], PREFIX_BLOCKS)
+        );
+    }
+}
@@ -0,0 +1,5 @@
+mod challenge_63;
+mod challenge_64;
+
+pub use challenge_63::{forge_message, ghash_polynomial, recover_hash_key_candidates, GcmMessage};
+pub use challenge_64::recover_hash_key_from_truncated_tag;
@@ -0,0 +1,149 @@
+//! The GCM "forbidden attack", recovering the authentication key from messages that reuse a nonce
+//!
+//! A tag is GHASH, a polynomial in the hash key H with the blocks as coefficients, plus the encrypted first counter
+//! block s. Two messages under the same key and nonce share s, so the difference of their polynomials with the tags
+//! as constant terms has H as a root. Factoring it leaves a handful of candidates, and with H and s known any
+//! message can be given a valid tag.
+
+use crate::{
+    gf128::{Gf128, Polynomial},
+    modes::{ghash, ghash_lengths_block},
+};
+
+/// A GCM message as it's sent, the additional data, cipher text and full 16 byte tag
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GcmMessage {
+    pub aad: Vec<u8>,
+    pub cipher_text: Vec<u8>,
+    pub tag: [u8; 16],
+}
+
+impl GcmMessage {
+    /// Splits the output of [gcm_seal](crate::modes::gcm_seal) with a full tag
+    ///
+    /// Returning None indicates the sealed text is shorter than a tag
+    pub fn from_sealed(aad: &[u8], sealed: &[u8]) -> Option<Self> {
+        let split = sealed.len().checked_sub(16)?;
+        let (cipher_text, tag) = sealed.split_at(split);
+        Some(Self {
+            aad: aad.to_vec(),
+            cipher_text: cipher_text.to_vec(),
+            tag: tag.try_into().unwrap(),
+        })
+    }
+
+    /// The cipher text followed by the tag, as [gcm_open](crate::modes::gcm_open) takes it
+    pub fn to_sealed(&self) -> Vec<u8> {
+        [self.cipher_text.as_slice(), &self.tag].concat()
+    }
+}
+
+/// The polynomial that evaluates to the encrypted first counter block s at H
+///
+/// The blocks GHASH multiplies in are the coefficients, the first block of the additional data the highest and the
+/// lengths block that of x, with the tag as the constant term.
+pub fn ghash_polynomial(message: &GcmMessage) -> Polynomial {
+    let lengths = ghash_lengths_block(&message.aad, &message.cipher_text);
+    let blocks = message
+        .aad
+        .chunks(16)
+        .chain(message.cipher_text.chunks(16))
+        .chain(std::iter::once(lengths.as_slice()))
+        .rev()
+        .map(Gf128::from_partial_block);
+    Polynomial::new(
+        std::iter::once(Gf128::from_bytes(&message.tag))
+            .chain(blocks)
+            .collect(),
+    )
+}
+
+/// Recovers the possible hash keys from messages sealed with the same key and nonce
+///
+/// The first pair of messages that differ is factored for its roots, every other message then rules out the
+/// candidates it doesn't agree with. Two messages usually leave a few candidates and three just the one.
+pub fn recover_hash_key_candidates(messages: &[GcmMessage]) -> Vec<Gf128> {
+    let Some((first, rest)) = messages.split_first() else {
+        return Vec::new();
+    };
+    let first = ghash_polynomial(first);
+    let differences: Vec<Polynomial> = rest
+        .iter()
+        .map(|message| &first - &ghash_polynomial(message))
+        .filter(|difference| !difference.is_zero())
+        .collect();
+    let Some((factored, others)) = differences.split_first() else {
+        return Vec::new();
+    };
+
+    let mut candidates = factored.roots().unwrap();
+    candidates.retain(|&h| others.iter().all(|other| other.evaluate(&h).is_zero()));
+    candidates
+}
+
+/// Gives new additional data and cipher text a valid tag under the same key and nonce as a known message
+///
+/// `h` is the hash key, the known message gives away the encrypted first counter block.
+pub fn forge_message(h: Gf128, known: &GcmMessage, aad: &[u8], cipher_text: &[u8]) -> GcmMessage {
+    let mask = Gf128::from_bytes(&known.tag) + ghash(h, &known.aad, &known.cipher_text);
+    GcmMessage {
+        aad: aad.to_vec(),
+        cipher_text: cipher_text.to_vec(),
+        tag: (ghash(h, aad, cipher_text) + mask).to_bytes(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::Aes128;
+
+    use super::*;
+    use crate::{
+        block_cipher::BlockCipher,
+        modes::{gcm_hash_key, gcm_open, gcm_seal},
+    };
+
+    #[test]
+    fn forbidden_attack() {
+        let cipher = <Aes128 as BlockCipher>::new(&rand::random());
+        let nonce: [u8; 12] = rand::random();
+        let seal = |aad: &[u8], plain_text: &[u8]| {
+            GcmMessage::from_sealed(
+                aad,
+                &gcm_seal(&cipher, &nonce, aad, plain_text, 16).unwrap(),
+            )
+            .unwrap()
+        };
+
+        let messages = [
+            seal(b"from alice", b"transfer 10 to bob"),
+            seal(b"from alice", b"transfer 20 to carol"),
+            seal(b"from bob", b"transfer 5 to alice, and thanks for lunch"),
+        ];
+        assert_eq!(GcmMessage::from_sealed(b"", &[0; 15]), None);
+        assert_eq!(
+            messages[0].to_sealed(),
+            gcm_seal(&cipher, &nonce, b"from alice", b"transfer 10 to bob", 16).unwrap()
+        );
+
+        let h = gcm_hash_key(&cipher).unwrap();
+        assert_eq!(
+            Gf128::ZERO,
+            (&ghash_polynomial(&messages[0]) - &ghash_polynomial(&messages[1])).evaluate(&h)
+        );
+        assert!(recover_hash_key_candidates(&messages[..2]).contains(&h));
+        let candidates = recover_hash_key_candidates(&messages);
+        assert_eq!(vec![h], candidates);
+
+        // Flip the cipher text to a different amount without the key, then give it a tag that checks out
+        let mut cipher_text = messages[0].cipher_text.clone();
+        for (c, (a, b)) in cipher_text[9..11].iter_mut().zip(b"10".iter().zip(b"99")) {
+            *c ^= a ^ b;
+        }
+        let forged = forge_message(candidates[0], &messages[1], b"from alice", &cipher_text);
+        assert_eq!(
+            Some(b"transfer 99 to bob".to_vec()),
+            gcm_open(&cipher, &nonce, b"from alice", &forged.to_sealed(), 16)
+        );
+    }
+}
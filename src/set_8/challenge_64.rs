@@ -0,0 +1,153 @@
+//! Ferguson's key recovery attack on GCM with short tags
+//!
+//! Changing only the cipher text blocks that GHASH multiplies by H^(2^i) changes the tag by sum d_i H^(2^i), and
+//! since squaring is linear that's a matrix Ad, built from the changes d_i, times H. Picking the changes so the first
+//! rows of Ad are zero forces those bits of the tag to stay the same, so a forgery is much more likely than one in
+//! 2^t. Each forgery that's accepted says the rest of the tag's rows of Ad times H are zero too, linear equations in
+//! the bits of H that in turn let more rows be zeroed, until H is the only non-zero solution.
+
+use rand::Rng;
+
+use crate::{gf128::Gf128, gf2::Gf2Matrix};
+
+/// Recovers the hash key from a cipher text whose tag is truncated to `tag_bits` bits
+///
+/// `oracle` is given copies of the cipher text with some blocks changed, and reports whether they are accepted with
+/// the original truncated tag, the same key, nonce and additional data. The cipher text must be whole blocks, the
+/// more of them there are the fewer forgeries are needed. With the 32 bit tags SP 800-38D allows that takes about
+/// 2^16 queries of a 2^17 block message for the first forgery and fewer for each one after.
+///
+/// Returning None indicates the cipher text is not at least one whole block, the tag length is not 1 to 128 bits,
+/// or the oracle accepted changes H doesn't fit
+pub fn recover_hash_key_from_truncated_tag<F: FnMut(&[u8]) -> bool>(
+    cipher_text: &[u8],
+    tag_bits: usize,
+    mut oracle: F,
+) -> Option<Gf128> {
+    let blocks = cipher_text.len() / 16;
+    if !cipher_text.len().is_multiple_of(16) || blocks == 0 || !(1..=128).contains(&tag_bits) {
+        return None;
+    }
+    // The last block is multiplied by H^2 and the one 2^i - 2 before it by H^(2^i)
+    let doublings = (blocks + 1).ilog2() as usize;
+    let squarings: Vec<Gf2Matrix> = std::iter::successors(Some(Gf128::squaring_matrix()), |s| {
+        Some(s * &Gf128::squaring_matrix())
+    })
+    .take(doublings)
+    .collect();
+    let x = Gf128::x_pow(1).multiplication_matrix();
+
+    let mut rng = rand::thread_rng();
+    let mut equations = Gf2Matrix::new(0, 128);
+    loop {
+        // H is a combination of these, which the next forgery only needs to work for
+        let basis = equations.kernel().transpose();
+        match basis.cols() {
+            0 => return None,
+            1 => return Some(Gf128::from_coefficients((0..128).map(|i| basis.get(i, 0)))),
+            _ => {}
+        }
+        let unknowns = doublings * 128;
+        let zeroed = (tag_bits - 1).min((unknowns - 1) / basis.cols());
+
+        // Column 128i + b is the effect on the zeroed rows of Ad times the basis of setting bit b of d_i
+        let mut dependence = Gf2Matrix::new(zeroed * basis.cols(), unknowns);
+        for (i, squaring) in squarings.iter().enumerate() {
+            let mut effect = squaring * &basis;
+            for b in 0..128 {
+                for row in 0..zeroed {
+                    for col in 0..basis.cols() {
+                        if effect.get(row, col) {
+                            dependence.set(row * basis.cols() + col, i * 128 + b, true);
+                        }
+                    }
+                }
+                effect = &x * &effect;
+            }
+        }
+        let changes = dependence.kernel();
+
+        let rank = equations.rank();
+        while equations.rank() == rank {
+            let pick = Gf2Matrix::from_fn(1, changes.rows(), |_, _| rng.gen());
+            let change = &pick * &changes;
+            let differences: Vec<Gf128> = (0..doublings)
+                .map(|i| Gf128::from_coefficients((0..128).map(|b| change.get(0, i * 128 + b))))
+                .collect();
+            if differences.iter().all(|d| d.is_zero()) {
+                continue;
+            }
+
+            let mut forged = cipher_text.to_vec();
+            for (i, d) in differences.iter().enumerate() {
+                let start = (blocks + 1 - (2 << i)) * 16;
+                for (c, d) in forged[start..start + 16].iter_mut().zip(d.to_bytes()) {
+                    *c ^= d;
+                }
+            }
+            if !oracle(&forged) {
+                continue;
+            }
+
+            let ad = differences
+                .iter()
+                .zip(&squarings)
+                .fold(Gf2Matrix::new(128, 128), |ad, (d, squaring)| {
+                    &ad + &(&d.multiplication_matrix() * squaring)
+                });
+            // The rest of the tag's rows of Ad times H are zero for the forgery to be accepted
+            let rows = equations.rows();
+            equations = Gf2Matrix::from_fn(rows + tag_bits - zeroed, 128, |row, col| {
+                if row < rows {
+                    equations.get(row, col)
+                } else {
+                    ad.get(row - rows + zeroed, col)
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::Aes128;
+
+    use super::*;
+    use crate::{
+        block_cipher::BlockCipher,
+        modes::{gcm_hash_key, gcm_seal},
+    };
+
+    /// An 8 bit tag, which SP 800-38D doesn't allow, keeps the number of forgeries down to something a test can do
+    #[test]
+    fn truncated_tag_key_recovery() {
+        let cipher = <Aes128 as BlockCipher>::new(&rand::random());
+        let nonce: [u8; 12] = rand::random();
+        let plain_text: Vec<u8> = (0..32 * 16).map(|_| rand::random()).collect();
+        let sealed = gcm_seal(&cipher, &nonce, b"", &plain_text, 16).unwrap();
+        let (cipher_text, tag) = sealed.split_at(plain_text.len());
+
+        let mut queries = 0;
+        let oracle = |forged: &[u8]| {
+            queries += 1;
+            // CTR mode flips the same bits of the plain text as of the cipher text
+            let forged_plain_text: Vec<u8> = plain_text
+                .iter()
+                .zip(cipher_text.iter().zip(forged))
+                .map(|(p, (c, f))| p ^ c ^ f)
+                .collect();
+            let resealed = gcm_seal(&cipher, &nonce, b"", &forged_plain_text, 16).unwrap();
+            resealed[forged.len()] == tag[0]
+        };
+
+        assert_eq!(
+            gcm_hash_key(&cipher),
+            recover_hash_key_from_truncated_tag(cipher_text, 8, oracle)
+        );
+        assert!(queries < 1000, "{queries} queries");
+        assert_eq!(
+            None,
+            recover_hash_key_from_truncated_tag(&[0; 17], 8, |_| true)
+        );
+    }
+}
@@ -0,0 +1,52 @@
+//! Encryption over [std::io::Read] and [std::io::Write], for texts too long to hold in memory
+
+mod block;
+mod keystream;
+
+pub use block::{
+    BlockDecryptReader, BlockEncryptWriter, CbcDecryptReader, CbcDecryptor, CbcEncryptWriter,
+    CbcEncryptor, EcbDecryptReader, EcbDecryptor, EcbEncryptWriter, EcbEncryptor, StreamBlockMode,
+};
+pub use keystream::{
+    CtrKeystream, CtrReader, CtrWriter, Keystream, KeystreamReader, KeystreamWriter,
+    XorRepeatKeystream, XorRepeatReader, XorRepeatWriter,
+};
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::io::{self, Write};
+
+    /// Fails once after accepting `fail_at` bytes, then accepts everything again
+    pub(crate) struct FailingWriter {
+        pub(crate) written: Vec<u8>,
+        pub(crate) fail_at: Option<usize>,
+    }
+
+    impl FailingWriter {
+        pub(crate) fn new(fail_at: usize) -> Self {
+            Self {
+                written: Vec::new(),
+                fail_at: Some(fail_at),
+            }
+        }
+    }
+
+    impl Write for FailingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let len = match self.fail_at {
+                Some(fail_at) if self.written.len() == fail_at && !buf.is_empty() => {
+                    self.fail_at = None;
+                    return Err(io::Error::other("transient failure"));
+                }
+                Some(fail_at) => buf.len().min(fail_at - self.written.len()),
+                None => buf.len(),
+            };
+            self.written.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,500 @@
+//! PKCS#7 padded block cipher modes over readers and writers
+
+use std::io::{self, ErrorKind, Read, Write};
+
+use crate::{
+    block_cipher::BlockCipher,
+    modes::{
+        cbc_decrypt_in_place, cbc_encrypt_in_place, ecb_decrypt_in_place, ecb_encrypt_in_place,
+    },
+    padding::{Padding, Pkcs7},
+};
+
+/// Bytes read from the inner reader at a time
+const READ_CHUNK_LEN: usize = 8 * 1024;
+
+/// One direction of a block cipher mode, applied to a stream a few blocks at a time
+pub trait StreamBlockMode {
+    /// Size of a block in bytes
+    fn block_size(&self) -> usize;
+
+    /// Encrypts or decrypts a whole number of blocks in place, carrying any chaining state on to the next call
+    fn process(&mut self, blocks: &mut [u8]);
+}
+
+/// ECB encryption
+pub struct EcbEncryptor<C> {
+    cipher: C,
+}
+
+impl<C: BlockCipher> StreamBlockMode for EcbEncryptor<C> {
+    fn block_size(&self) -> usize {
+        C::BLOCK_SIZE
+    }
+
+    fn process(&mut self, blocks: &mut [u8]) {
+        ecb_encrypt_in_place(&self.cipher, blocks).unwrap();
+    }
+}
+
+/// ECB decryption
+pub struct EcbDecryptor<C> {
+    cipher: C,
+}
+
+impl<C: BlockCipher> StreamBlockMode for EcbDecryptor<C> {
+    fn block_size(&self) -> usize {
+        C::BLOCK_SIZE
+    }
+
+    fn process(&mut self, blocks: &mut [u8]) {
+        ecb_decrypt_in_place(&self.cipher, blocks).unwrap();
+    }
+}
+
+/// CBC encryption, the last cipher text block is the IV for the next call
+pub struct CbcEncryptor<C> {
+    cipher: C,
+    previous_block: Vec<u8>,
+}
+
+impl<C: BlockCipher> StreamBlockMode for CbcEncryptor<C> {
+    fn block_size(&self) -> usize {
+        C::BLOCK_SIZE
+    }
+
+    fn process(&mut self, blocks: &mut [u8]) {
+        if blocks.is_empty() {
+            return;
+        }
+        cbc_encrypt_in_place(&self.cipher, &self.previous_block, blocks).unwrap();
+        self.previous_block
+            .copy_from_slice(&blocks[(blocks.len() - C::BLOCK_SIZE)..]);
+    }
+}
+
+/// CBC decryption, the last cipher text block is the IV for the next call
+pub struct CbcDecryptor<C> {
+    cipher: C,
+    previous_block: Vec<u8>,
+}
+
+impl<C: BlockCipher> StreamBlockMode for CbcDecryptor<C> {
+    fn block_size(&self) -> usize {
+        C::BLOCK_SIZE
+    }
+
+    fn process(&mut self, blocks: &mut [u8]) {
+        if blocks.is_empty() {
+            return;
+        }
+        let last_block = blocks[(blocks.len() - C::BLOCK_SIZE)..].to_vec();
+        cbc_decrypt_in_place(&self.cipher, &self.previous_block, blocks).unwrap();
+        self.previous_block = last_block;
+    }
+}
+
+/// Encrypts everything written to it and writes the cipher text on to the inner writer
+///
+/// Whole blocks are written as soon as they're complete, a partial block is carried over to the next write. The
+/// final block is padded with PKCS#7 by [BlockEncryptWriter::finish], or when the writer is dropped, which ignores
+/// any error.
+///
+/// The mode has moved on by the time the inner writer fails, so the first error is returned again by every later
+/// write, flush and finish rather than writing cipher text that no longer lines up.
+pub struct BlockEncryptWriter<M: StreamBlockMode, W: Write> {
+    mode: M,
+    writer: Option<W>,
+    partial_block: Vec<u8>,
+    finished: bool,
+    error: Option<ErrorKind>,
+}
+
+/// Encrypts a stream with ECB
+pub type EcbEncryptWriter<C, W> = BlockEncryptWriter<EcbEncryptor<C>, W>;
+
+/// Encrypts a stream with CBC
+pub type CbcEncryptWriter<C, W> = BlockEncryptWriter<CbcEncryptor<C>, W>;
+
+impl<M: StreamBlockMode, W: Write> BlockEncryptWriter<M, W> {
+    pub fn with_mode(mode: M, writer: W) -> Self {
+        Self {
+            mode,
+            writer: Some(writer),
+            partial_block: Vec::new(),
+            finished: false,
+            error: None,
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        self.writer.as_ref().unwrap()
+    }
+
+    /// Pads and writes the final block, then flushes and returns the inner writer
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_final_block()?;
+        Ok(self.writer.take().unwrap())
+    }
+
+    fn write_final_block(&mut self) -> io::Result<()> {
+        self.check_error()?;
+        if self.finished {
+            return Ok(());
+        }
+        // Processing the block moves the mode on, so it's never retried
+        self.finished = true;
+
+        let block_size = self.mode.block_size();
+        let mut final_block = Pkcs7.pad(&self.partial_block, block_size).ok_or_else(|| {
+            io::Error::new(ErrorKind::InvalidInput, "PKCS#7 can't pad blocks this long")
+        })?;
+        self.partial_block.clear();
+        self.mode.process(&mut final_block);
+
+        self.write_inner(&final_block)?;
+        self.flush()
+    }
+
+    /// Returns the error the inner writer failed with, if it has
+    fn check_error(&self) -> io::Result<()> {
+        match self.error {
+            Some(kind) => Err(kind.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes on to the inner writer
+    fn write_inner(&mut self, cipher_text: &[u8]) -> io::Result<()> {
+        let result = self.writer.as_mut().unwrap().write_all(cipher_text);
+        self.record_error(result)
+    }
+
+    /// Fails this and every later write if the inner writer fails
+    fn record_error<T>(&mut self, result: io::Result<T>) -> io::Result<T> {
+        if let Err(e) = &result {
+            self.error = Some(e.kind());
+        }
+        result
+    }
+}
+
+impl<C: BlockCipher, W: Write> BlockEncryptWriter<EcbEncryptor<C>, W> {
+    pub fn new(cipher: C, writer: W) -> Self {
+        Self::with_mode(EcbEncryptor { cipher }, writer)
+    }
+}
+
+impl<C: BlockCipher, W: Write> BlockEncryptWriter<CbcEncryptor<C>, W> {
+    /// Returning None indicates the IV is not a single block
+    pub fn new(cipher: C, iv: &[u8], writer: W) -> Option<Self> {
+        if iv.len() != C::BLOCK_SIZE {
+            return None;
+        }
+
+        let mode = CbcEncryptor {
+            cipher,
+            previous_block: iv.to_vec(),
+        };
+        Some(Self::with_mode(mode, writer))
+    }
+}
+
+impl<M: StreamBlockMode, W: Write> Write for BlockEncryptWriter<M, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_error()?;
+        let block_size = self.mode.block_size();
+
+        let mut blocks = std::mem::take(&mut self.partial_block);
+        blocks.extend_from_slice(buf);
+        self.partial_block = blocks.split_off(blocks.len() / block_size * block_size);
+
+        if !blocks.is_empty() {
+            self.mode.process(&mut blocks);
+            self.write_inner(&blocks)?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.check_error()?;
+        let result = self.writer.as_mut().unwrap().flush();
+        self.record_error(result)
+    }
+}
+
+impl<M: StreamBlockMode, W: Write> Drop for BlockEncryptWriter<M, W> {
+    fn drop(&mut self) {
+        if self.writer.is_some() && self.error.is_none() {
+            let _ = self.write_final_block();
+        }
+    }
+}
+
+/// Reads cipher text from the inner reader and decrypts it
+///
+/// The last block read is held back until the end of the stream, where its PKCS#7 padding is checked and removed.
+/// Cipher text that isn't a whole number of blocks or has invalid padding is an [ErrorKind::InvalidData] error, which
+/// every later read returns again.
+pub struct BlockDecryptReader<M: StreamBlockMode, R: Read> {
+    mode: M,
+    reader: R,
+    cipher_text: Vec<u8>,
+    plain_text: Vec<u8>,
+    plain_text_pos: usize,
+    eof: bool,
+    error: Option<ErrorKind>,
+}
+
+/// Decrypts a stream with ECB
+pub type EcbDecryptReader<C, R> = BlockDecryptReader<EcbDecryptor<C>, R>;
+
+/// Decrypts a stream with CBC
+pub type CbcDecryptReader<C, R> = BlockDecryptReader<CbcDecryptor<C>, R>;
+
+impl<M: StreamBlockMode, R: Read> BlockDecryptReader<M, R> {
+    pub fn with_mode(mode: M, reader: R) -> Self {
+        Self {
+            mode,
+            reader,
+            cipher_text: Vec::new(),
+            plain_text: Vec::new(),
+            plain_text_pos: 0,
+            eof: false,
+            error: None,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads more cipher text and decrypts every block but the last
+    fn fill_plain_text(&mut self) -> io::Result<()> {
+        let block_size = self.mode.block_size();
+
+        let mut chunk = [0; READ_CHUNK_LEN];
+        let read_len = loop {
+            match self.reader.read(&mut chunk) {
+                Ok(read_len) => break read_len,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        };
+        self.cipher_text.extend_from_slice(&chunk[..read_len]);
+
+        if read_len == 0 {
+            self.eof = true;
+            if self.cipher_text.is_empty() || !self.cipher_text.len().is_multiple_of(block_size) {
+                return Err(self.invalid_data("cipher text is not a whole number of blocks"));
+            }
+
+            // Only replace the plain text once the padding checks out, so a failed read leaves nothing to return
+            let mut final_block = std::mem::take(&mut self.cipher_text);
+            self.mode.process(&mut final_block);
+            let unpadded_len = Pkcs7
+                .unpadded_len(&final_block, block_size)
+                .ok_or_else(|| self.invalid_data("invalid PKCS#7 padding"))?;
+            final_block.truncate(unpadded_len);
+            self.plain_text = final_block;
+        } else {
+            let blocks_len = self.cipher_text.len().saturating_sub(1) / block_size * block_size;
+            let rest = self.cipher_text.split_off(blocks_len);
+            self.plain_text = std::mem::replace(&mut self.cipher_text, rest);
+            self.mode.process(&mut self.plain_text);
+        }
+
+        self.plain_text_pos = 0;
+        Ok(())
+    }
+
+    /// Fails this and every later read
+    fn invalid_data(&mut self, message: &str) -> io::Error {
+        self.error = Some(ErrorKind::InvalidData);
+        io::Error::new(ErrorKind::InvalidData, message)
+    }
+}
+
+impl<C: BlockCipher, R: Read> BlockDecryptReader<EcbDecryptor<C>, R> {
+    pub fn new(cipher: C, reader: R) -> Self {
+        Self::with_mode(EcbDecryptor { cipher }, reader)
+    }
+}
+
+impl<C: BlockCipher, R: Read> BlockDecryptReader<CbcDecryptor<C>, R> {
+    /// Returning None indicates the IV is not a single block
+    pub fn new(cipher: C, iv: &[u8], reader: R) -> Option<Self> {
+        if iv.len() != C::BLOCK_SIZE {
+            return None;
+        }
+
+        let mode = CbcDecryptor {
+            cipher,
+            previous_block: iv.to_vec(),
+        };
+        Some(Self::with_mode(mode, reader))
+    }
+}
+
+impl<M: StreamBlockMode, R: Read> Read for BlockDecryptReader<M, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.plain_text_pos == self.plain_text.len() {
+            if let Some(kind) = self.error {
+                return Err(kind.into());
+            }
+            if self.eof {
+                return Ok(0);
+            }
+            self.fill_plain_text()?;
+        }
+
+        let available = &self.plain_text[self.plain_text_pos..];
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.plain_text_pos += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::Aes128;
+
+    use super::*;
+    use crate::{
+        block_cipher::tests::ToyCipher,
+        modes::{cbc_encrypt, cbc_encrypt_padded},
+        padding::Pkcs7,
+        stream::tests::FailingWriter,
+    };
+
+    /// Hands out at most `max_len` bytes per read, to split blocks across reads
+    struct ShortReader<'a> {
+        bytes: &'a [u8],
+        max_len: usize,
+    }
+
+    impl Read for ShortReader<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.bytes.len().min(buf.len()).min(self.max_len);
+            buf[..len].copy_from_slice(&self.bytes[..len]);
+            self.bytes = &self.bytes[len..];
+            Ok(len)
+        }
+    }
+
+    fn aes() -> Aes128 {
+        <Aes128 as BlockCipher>::new(b"YELLOW SUBMARINE")
+    }
+
+    #[test]
+    fn cbc_round_trip() {
+        let iv = [3; 16];
+        let plain_text = (0..20_000).map(|i| (i * 31) as u8).collect::<Vec<u8>>();
+        let expected = cbc_encrypt_padded(&aes(), &iv, &plain_text, &Pkcs7).unwrap();
+
+        for write_len in [1, 7, 16, 1000, 20_000] {
+            let mut writer = CbcEncryptWriter::new(aes(), &iv, Vec::new()).unwrap();
+            for chunk in plain_text.chunks(write_len) {
+                writer.write_all(chunk).unwrap();
+            }
+            assert_eq!(expected, writer.finish().unwrap());
+        }
+
+        for max_len in [1, 15, 17, 20_000] {
+            let reader = ShortReader {
+                bytes: &expected,
+                max_len,
+            };
+            let mut decrypted = Vec::new();
+            CbcDecryptReader::new(aes(), &iv, reader)
+                .unwrap()
+                .read_to_end(&mut decrypted)
+                .unwrap();
+            assert_eq!(plain_text, decrypted);
+        }
+    }
+
+    #[test]
+    fn ecb_round_trip() {
+        let plain_text = b"YELLOW SUBMARINE, not quite two blocks".to_vec();
+
+        let mut cipher_text = Vec::new();
+        {
+            // Dropping the writer writes the final block
+            let mut writer = EcbEncryptWriter::new(ToyCipher::new(b"toy key!"), &mut cipher_text);
+            writer.write_all(&plain_text).unwrap();
+        }
+        assert_eq!(40, cipher_text.len());
+
+        let mut decrypted = Vec::new();
+        EcbDecryptReader::new(ToyCipher::new(b"toy key!"), cipher_text.as_slice())
+            .read_to_end(&mut decrypted)
+            .unwrap();
+        assert_eq!(plain_text, decrypted);
+    }
+
+    #[test]
+    fn invalid_cipher_text() {
+        let iv = [3; 16];
+        let read = |cipher_text: &[u8]| {
+            CbcDecryptReader::new(aes(), &iv, cipher_text)
+                .unwrap()
+                .read_to_end(&mut Vec::new())
+                .map_err(|e| e.kind())
+        };
+
+        let cipher_text = cbc_encrypt_padded(&aes(), &iv, b"padded", &Pkcs7).unwrap();
+        assert_eq!(Ok(6), read(&cipher_text));
+        assert_eq!(Err(ErrorKind::InvalidData), read(&cipher_text[1..]));
+        assert_eq!(Err(ErrorKind::InvalidData), read(&[]));
+
+        let unpadded = cbc_encrypt(&aes(), &iv, &[0; 16]).unwrap();
+        assert_eq!(Err(ErrorKind::InvalidData), read(&unpadded));
+
+        // The error sticks, rather than a retry returning the bad block or looking like a clean end of stream
+        let mut long_unpadded = cipher_text.clone();
+        long_unpadded.extend(cbc_encrypt(&aes(), &cipher_text, &[0; 16]).unwrap());
+        for cipher_text in [&cipher_text[1..], &unpadded, &long_unpadded] {
+            let mut reader = CbcDecryptReader::new(aes(), &iv, cipher_text).unwrap();
+            let mut buf = [0; 64];
+            if cipher_text.len() > 16 {
+                // The blocks before the last decrypt fine
+                assert_eq!(16, reader.read(&mut buf).unwrap());
+            }
+            for _ in 0..3 {
+                assert_eq!(
+                    Err(ErrorKind::InvalidData),
+                    reader.read(&mut buf).map_err(|e| e.kind())
+                );
+            }
+        }
+
+        assert!(CbcEncryptWriter::new(aes(), &[0; 8], Vec::new()).is_none());
+    }
+
+    #[test]
+    fn failing_writer() {
+        let mut inner = FailingWriter::new(40);
+        let mut writer = CbcEncryptWriter::new(aes(), &[3; 16], &mut inner).unwrap();
+        writer.write_all(&[0; 32]).unwrap();
+        assert!(writer.write_all(&[0; 32]).is_err());
+
+        // The inner writer has recovered, but the error sticks rather than writing blocks chained on from one that
+        // was never written
+        for _ in 0..3 {
+            assert!(writer.write(&[0; 16]).is_err());
+            assert!(writer.flush().is_err());
+        }
+        assert!(writer.finish().is_err());
+        assert_eq!(40, inner.written.len());
+
+        // Dropping a failed writer doesn't write the final block
+        let mut inner = FailingWriter::new(16);
+        let mut writer = EcbEncryptWriter::new(aes(), &mut inner);
+        assert!(writer.write_all(&[0; 32]).is_err());
+        drop(writer);
+        assert_eq!(16, inner.written.len());
+    }
+}
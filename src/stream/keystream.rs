@@ -0,0 +1,297 @@
+//! Keystream ciphers over readers and writers, CTR and repeating key XOR
+
+use std::io::{self, ErrorKind, Read, Write};
+
+use crate::{block_cipher::BlockCipher, modes::ctr_apply_keystream};
+
+/// A keystream XORed into a stream, picking up where the last call left off
+pub trait Keystream {
+    /// XORs the next `text.len()` bytes of keystream into the text
+    fn apply(&mut self, text: &mut [u8]);
+}
+
+/// The CTR keystream, in the cryptopals counter format
+pub struct CtrKeystream<C> {
+    cipher: C,
+    nonce: u64,
+    block_count: u64,
+    /// The current keystream block, and how much of it is used
+    block: [u8; 16],
+    block_used: usize,
+}
+
+impl<C: BlockCipher> CtrKeystream<C> {
+    /// Returning None indicates the cipher's blocks are not 16 bytes
+    pub fn new(cipher: C, nonce: u64) -> Option<Self> {
+        if C::BLOCK_SIZE != 16 {
+            return None;
+        }
+
+        Some(Self {
+            cipher,
+            nonce,
+            block_count: 0,
+            block: [0; 16],
+            block_used: 16,
+        })
+    }
+}
+
+impl<C: BlockCipher> Keystream for CtrKeystream<C> {
+    fn apply(&mut self, text: &mut [u8]) {
+        // Use up the current block, then whole blocks, then start a new block for the rest
+        let leftover_len = (16 - self.block_used).min(text.len());
+        let (leftover, text) = text.split_at_mut(leftover_len);
+        for (byte, keystream_byte) in leftover.iter_mut().zip(&self.block[self.block_used..]) {
+            *byte ^= keystream_byte;
+        }
+        self.block_used += leftover_len;
+
+        let (blocks, rest) = text.split_at_mut(text.len() / 16 * 16);
+        ctr_apply_keystream(&self.cipher, self.nonce, self.block_count, blocks).unwrap();
+        self.block_count += (blocks.len() / 16) as u64;
+
+        if !rest.is_empty() {
+            self.block = [0; 16];
+            ctr_apply_keystream(&self.cipher, self.nonce, self.block_count, &mut self.block)
+                .unwrap();
+            self.block_count += 1;
+
+            for (byte, keystream_byte) in rest.iter_mut().zip(self.block) {
+                *byte ^= keystream_byte;
+            }
+            self.block_used = rest.len();
+        }
+    }
+}
+
+/// The repeating key XOR keystream
+pub struct XorRepeatKeystream {
+    key: Vec<u8>,
+    position: usize,
+}
+
+impl XorRepeatKeystream {
+    /// Returning None indicates the key is empty
+    pub fn new(key: &[u8]) -> Option<Self> {
+        if key.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            key: key.to_vec(),
+            position: 0,
+        })
+    }
+}
+
+impl Keystream for XorRepeatKeystream {
+    fn apply(&mut self, text: &mut [u8]) {
+        for byte in text {
+            *byte ^= self.key[self.position];
+            self.position = (self.position + 1) % self.key.len();
+        }
+    }
+}
+
+/// XORs the keystream into everything written to it and writes the result on to the inner writer
+///
+/// The keystream has moved on by the time the inner writer fails, so the first error is returned again by every later
+/// write and flush.
+pub struct KeystreamWriter<K: Keystream, W: Write> {
+    keystream: K,
+    writer: W,
+    buffer: Vec<u8>,
+    error: Option<ErrorKind>,
+}
+
+/// Encrypts or decrypts a stream with CTR
+pub type CtrWriter<C, W> = KeystreamWriter<CtrKeystream<C>, W>;
+
+/// Encrypts or decrypts a stream with repeating key XOR
+pub type XorRepeatWriter<W> = KeystreamWriter<XorRepeatKeystream, W>;
+
+impl<K: Keystream, W: Write> KeystreamWriter<K, W> {
+    pub fn with_keystream(keystream: K, writer: W) -> Self {
+        Self {
+            keystream,
+            writer,
+            buffer: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Returns the error the inner writer failed with, if it has
+    fn check_error(&self) -> io::Result<()> {
+        match self.error {
+            Some(kind) => Err(kind.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails this and every later write if the inner writer fails
+    fn record_error<T>(&mut self, result: io::Result<T>) -> io::Result<T> {
+        if let Err(e) = &result {
+            self.error = Some(e.kind());
+        }
+        result
+    }
+}
+
+impl<C: BlockCipher, W: Write> KeystreamWriter<CtrKeystream<C>, W> {
+    /// Returning None indicates the cipher's blocks are not 16 bytes
+    pub fn new(cipher: C, nonce: u64, writer: W) -> Option<Self> {
+        Some(Self::with_keystream(
+            CtrKeystream::new(cipher, nonce)?,
+            writer,
+        ))
+    }
+}
+
+impl<W: Write> KeystreamWriter<XorRepeatKeystream, W> {
+    /// Returning None indicates the key is empty
+    pub fn new(key: &[u8], writer: W) -> Option<Self> {
+        Some(Self::with_keystream(XorRepeatKeystream::new(key)?, writer))
+    }
+}
+
+impl<K: Keystream, W: Write> Write for KeystreamWriter<K, W> {
+    /// The keystream moves on as soon as the bytes are taken, so they're always written in full
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.check_error()?;
+        self.buffer.clear();
+        self.buffer.extend_from_slice(buf);
+        self.keystream.apply(&mut self.buffer);
+        let result = self.writer.write_all(&self.buffer);
+        self.record_error(result)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.check_error()?;
+        let result = self.writer.flush();
+        self.record_error(result)
+    }
+}
+
+/// Reads from the inner reader and XORs the keystream into it
+pub struct KeystreamReader<K: Keystream, R: Read> {
+    keystream: K,
+    reader: R,
+}
+
+/// Encrypts or decrypts a stream with CTR
+pub type CtrReader<C, R> = KeystreamReader<CtrKeystream<C>, R>;
+
+/// Encrypts or decrypts a stream with repeating key XOR
+pub type XorRepeatReader<R> = KeystreamReader<XorRepeatKeystream, R>;
+
+impl<K: Keystream, R: Read> KeystreamReader<K, R> {
+    pub fn with_keystream(keystream: K, reader: R) -> Self {
+        Self { keystream, reader }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<C: BlockCipher, R: Read> KeystreamReader<CtrKeystream<C>, R> {
+    /// Returning None indicates the cipher's blocks are not 16 bytes
+    pub fn new(cipher: C, nonce: u64, reader: R) -> Option<Self> {
+        Some(Self::with_keystream(
+            CtrKeystream::new(cipher, nonce)?,
+            reader,
+        ))
+    }
+}
+
+impl<R: Read> KeystreamReader<XorRepeatKeystream, R> {
+    /// Returning None indicates the key is empty
+    pub fn new(key: &[u8], reader: R) -> Option<Self> {
+        Some(Self::with_keystream(XorRepeatKeystream::new(key)?, reader))
+    }
+}
+
+impl<K: Keystream, R: Read> Read for KeystreamReader<K, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.keystream.apply(&mut buf[..len]);
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use aes::Aes128;
+
+    use super::*;
+    use crate::{set_1::xor_repeat, set_3::aes_128_ctr, stream::tests::FailingWriter};
+
+    #[test]
+    fn ctr() {
+        let key = *b"YELLOW SUBMARINE";
+        let plain_text = (0..5000).map(|i| (i * 13) as u8).collect::<Vec<u8>>();
+        let expected = aes_128_ctr(5, &plain_text, key);
+
+        for write_len in [1, 5, 16, 33, 5000] {
+            let mut writer =
+                CtrWriter::new(<Aes128 as BlockCipher>::new(&key), 5, Vec::new()).unwrap();
+            for chunk in plain_text.chunks(write_len) {
+                writer.write_all(chunk).unwrap();
+            }
+            assert_eq!(expected, writer.into_inner());
+        }
+
+        let mut decrypted = Vec::new();
+        CtrReader::new(<Aes128 as BlockCipher>::new(&key), 5, expected.as_slice())
+            .unwrap()
+            .read_to_end(&mut decrypted)
+            .unwrap();
+        assert_eq!(plain_text, decrypted);
+    }
+
+    #[test]
+    fn xor_repeat_stream() {
+        let plain_text =
+            b"Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal";
+        let expected = xor_repeat(plain_text, b"ICE").unwrap();
+
+        let mut writer = XorRepeatWriter::new(b"ICE", Vec::new()).unwrap();
+        for chunk in plain_text.chunks(4) {
+            writer.write_all(chunk).unwrap();
+        }
+        assert_eq!(expected, writer.into_inner());
+
+        let mut decrypted = Vec::new();
+        XorRepeatReader::new(b"ICE", expected.as_slice())
+            .unwrap()
+            .read_to_end(&mut decrypted)
+            .unwrap();
+        assert_eq!(plain_text.to_vec(), decrypted);
+        assert!(XorRepeatReader::new(b"", expected.as_slice()).is_none());
+    }
+
+    #[test]
+    fn failing_writer() {
+        let mut inner = FailingWriter::new(10);
+        let mut writer = XorRepeatWriter::new(b"ICE", &mut inner).unwrap();
+        assert!(writer.write_all(b"Burning 'em").is_err());
+
+        // The inner writer has recovered, but the keystream has already moved past the unwritten bytes
+        for _ in 0..3 {
+            assert!(writer.write(b"if you ain't quick").is_err());
+            assert!(writer.flush().is_err());
+        }
+        drop(writer);
+        assert_eq!(xor_repeat(b"Burning 'e", b"ICE").unwrap(), inner.written);
+    }
+}